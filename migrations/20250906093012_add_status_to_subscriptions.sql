@@ -0,0 +1,5 @@
+-- 为订阅者增加状态字段：pending_confirmation / confirmed
+-- 迁移在单个事务内执行，历史数据视为已确认
+ALTER TABLE subscriptions ADD COLUMN status TEXT NULL;
+UPDATE subscriptions SET status = 'confirmed' WHERE status IS NULL;
+ALTER TABLE subscriptions ALTER COLUMN status SET NOT NULL;
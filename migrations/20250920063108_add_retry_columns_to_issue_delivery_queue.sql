@@ -0,0 +1,5 @@
+-- 投递重试：已尝试次数、下一次可执行时间与最近一次错误
+ALTER TABLE issue_delivery_queue
+    ADD COLUMN n_retries INT NOT NULL DEFAULT 0,
+    ADD COLUMN execute_after timestamptz NOT NULL DEFAULT now(),
+    ADD COLUMN last_error TEXT NULL;
@@ -0,0 +1,7 @@
+-- 管理员账户，password_hash 为 Argon2id 的 PHC 字符串
+-- 账户需由运维直接写入，例如使用 `authentication::compute_password_hash` 生成哈希
+CREATE TABLE users (
+    user_id uuid PRIMARY KEY,
+    username TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL
+);
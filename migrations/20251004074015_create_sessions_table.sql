@@ -0,0 +1,8 @@
+-- 服务端会话存储：cookie 中只携带随机会话键，会话状态保存在此表
+-- 过期会话在读取时被忽略，并在创建新会话时顺带清理
+CREATE TABLE sessions (
+    session_key TEXT PRIMARY KEY,
+    state JSONB NOT NULL,
+    expires_at timestamptz NOT NULL
+);
+CREATE INDEX sessions_expires_at_idx ON sessions (expires_at);
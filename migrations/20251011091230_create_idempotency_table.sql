@@ -0,0 +1,16 @@
+-- 幂等键：同一用户使用同一 `Idempotency-Key` 的重复请求直接返回首次保存的响应
+-- 首个请求先插入响应为空的占位行并持有行锁直到事务提交，并发的重复请求会在插入时等待
+CREATE TYPE header_pair AS (
+    name TEXT,
+    value BYTEA
+);
+CREATE TABLE idempotency (
+    user_id uuid NOT NULL REFERENCES users(user_id),
+    idempotency_key TEXT NOT NULL,
+    response_status_code SMALLINT NULL,
+    response_headers header_pair[] NULL,
+    response_body BYTEA NULL,
+    created_at timestamptz NOT NULL,
+    PRIMARY KEY(user_id, idempotency_key)
+);
+CREATE INDEX idempotency_created_at_idx ON idempotency (created_at);
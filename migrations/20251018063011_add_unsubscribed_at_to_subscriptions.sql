@@ -0,0 +1,2 @@
+-- 退订后 status 置为 'unsubscribed'，并记录退订时间
+ALTER TABLE subscriptions ADD COLUMN unsubscribed_at timestamptz NULL;
@@ -0,0 +1,30 @@
+-- 邮箱按不区分大小写去重：回填已有数据并以 lower(email) 建唯一索引
+-- 回填只做应用层规范化中能在 SQL 里完成的部分（去除首尾空白、域名转小写），
+-- 国际化域名的 punycode 转换只对新写入的数据生效
+
+-- 规范化后会冲突的地址无法自动合并（涉及确认状态与令牌），列出后中止迁移，由运维人工处理
+DO $$
+DECLARE
+    collisions TEXT;
+BEGIN
+    SELECT string_agg(normalized || ' <- ' || originals, E'\n')
+    INTO collisions
+    FROM (
+        SELECT lower(btrim(email)) AS normalized, string_agg(email, ', ' ORDER BY email) AS originals
+        FROM subscriptions
+        GROUP BY lower(btrim(email))
+        HAVING count(*) > 1
+    ) AS duplicates;
+
+    IF collisions IS NOT NULL THEN
+        RAISE EXCEPTION E'Subscriber emails collide after case-insensitive normalization:\n%', collisions;
+    END IF;
+END
+$$;
+
+UPDATE subscriptions
+SET email = split_part(btrim(email), '@', 1) || '@' || lower(split_part(btrim(email), '@', 2))
+WHERE email LIKE '%@%' AND email NOT LIKE '%@%@%';
+
+ALTER TABLE subscriptions DROP CONSTRAINT subscriptions_email_key;
+CREATE UNIQUE INDEX subscriptions_email_lower_idx ON subscriptions (lower(email));
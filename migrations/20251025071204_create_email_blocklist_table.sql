@@ -0,0 +1,10 @@
+-- 管理员维护的订阅黑名单：命中的地址既不能订阅，也不会收到任何邮件
+-- kind = 'address'：完整地址；'domain'：域名及其所有子域名；'pattern'：使用 `*` 通配的地址
+-- value 写入前已规范化为小写
+CREATE TABLE email_blocklist (
+    id uuid NOT NULL PRIMARY KEY,
+    kind TEXT NOT NULL CHECK (kind IN ('address', 'domain', 'pattern')),
+    value TEXT NOT NULL,
+    created_at timestamptz NOT NULL,
+    UNIQUE (kind, value)
+);
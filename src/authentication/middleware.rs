@@ -0,0 +1,150 @@
+use super::password::{validate_credentials, AuthError, Credentials};
+use super::session::TypedSession;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::error::{ErrorInternalServerError, InternalError};
+use actix_web::http::header::{self, HeaderMap, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{web, HttpMessage, HttpResponse};
+use base64::Engine;
+use sqlx::PgPool;
+use std::ops::Deref;
+use uuid::Uuid;
+
+/// 通过认证的用户 id，由认证中间件写入请求扩展
+#[derive(Copy, Clone, Debug)]
+pub struct UserId(Uuid);
+
+impl UserId {
+    pub fn new(user_id: Uuid) -> Self {
+        Self(user_id)
+    }
+}
+
+impl std::fmt::Display for UserId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Deref for UserId {
+    type Target = Uuid;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// HTTP Basic 认证中间件：校验 `Authorization` 头，失败时返回 401 并携带 `WWW-Authenticate`
+pub async fn basic_authentication(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let user_id = authenticate_with_basic_credentials(&req).await?;
+    req.extensions_mut().insert(user_id);
+    next.call(req).await
+}
+
+/// `/admin` 下的认证中间件：优先使用登录会话；
+/// 未登录但携带 `Authorization` 头的请求（脚本、API 调用）按 Basic 认证处理，
+/// 其余请求重定向到登录页
+pub async fn reject_anonymous_users(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let session = req.extract::<TypedSession>().await?;
+    let user_id = match session.get_user_id().map_err(ErrorInternalServerError)? {
+        Some(user_id) => UserId(user_id),
+        None if req.headers().contains_key(header::AUTHORIZATION) => {
+            authenticate_with_basic_credentials(&req).await?
+        }
+        None => {
+            let response = HttpResponse::SeeOther()
+                .insert_header((header::LOCATION, "/login"))
+                .finish();
+            return Err(InternalError::from_response("The user has not logged in", response).into());
+        }
+    };
+    req.extensions_mut().insert(user_id);
+    next.call(req).await
+}
+
+async fn authenticate_with_basic_credentials(req: &ServiceRequest) -> Result<UserId, actix_web::Error> {
+    let credentials = basic_authentication_credentials(req.headers()).map_err(unauthorized)?;
+    let pool = req
+        .app_data::<web::Data<PgPool>>()
+        .ok_or_else(|| ErrorInternalServerError("Missing database pool"))?;
+
+    match validate_credentials(credentials, pool).await {
+        Ok(user_id) => Ok(UserId(user_id)),
+        Err(AuthError::InvalidCredentials) => Err(unauthorized("Invalid username or password.".into())),
+        Err(AuthError::Unexpected(e)) => {
+            tracing::error!("Failed to validate credentials: {}", e);
+            Err(ErrorInternalServerError(e))
+        }
+    }
+}
+
+fn unauthorized(reason: String) -> actix_web::Error {
+    let response = HttpResponse::Unauthorized()
+        .insert_header((
+            header::WWW_AUTHENTICATE,
+            HeaderValue::from_static(r#"Basic realm="admin""#),
+        ))
+        .finish();
+    InternalError::from_response(reason, response).into()
+}
+
+/// 解析 `Authorization: Basic base64(username:password)`
+fn basic_authentication_credentials(headers: &HeaderMap) -> Result<Credentials, String> {
+    let header_value = headers
+        .get(header::AUTHORIZATION)
+        .ok_or("The 'Authorization' header was missing")?
+        .to_str()
+        .map_err(|_| "The 'Authorization' header was not a valid UTF8 string.")?;
+    let base64encoded_segment = header_value
+        .strip_prefix("Basic ")
+        .ok_or("The authorization scheme was not 'Basic'.")?;
+    let decoded_bytes = base64::engine::general_purpose::STANDARD
+        .decode(base64encoded_segment)
+        .map_err(|_| "Failed to base64-decode 'Basic' credentials.")?;
+    let decoded_credentials = String::from_utf8(decoded_bytes)
+        .map_err(|_| "The decoded credential string is not valid UTF8.")?;
+
+    let (username, password) = decoded_credentials
+        .split_once(':')
+        .ok_or("A username and a password must be provided in 'Basic' auth.")?;
+    Ok(Credentials {
+        username: username.to_string(),
+        password: password.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn valid_basic_credentials_are_parsed() {
+        // "admin:pass:word" —— 密码中允许出现冒号
+        let credentials = basic_authentication_credentials(&headers("Basic YWRtaW46cGFzczp3b3Jk"))
+            .unwrap_or_else(|e| panic!("{}", e));
+        assert_eq!(credentials.username, "admin");
+        assert_eq!(credentials.password, "pass:word");
+    }
+
+    #[test]
+    fn missing_or_malformed_headers_are_rejected() {
+        assert!(basic_authentication_credentials(&HeaderMap::new()).is_err());
+        assert!(basic_authentication_credentials(&headers("Bearer token")).is_err());
+        assert!(basic_authentication_credentials(&headers("Basic not-base64!")).is_err());
+        // "admin" —— 缺少冒号分隔
+        assert!(basic_authentication_credentials(&headers("Basic YWRtaW4=")).is_err());
+    }
+}
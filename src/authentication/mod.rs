@@ -0,0 +1,7 @@
+mod middleware;
+mod password;
+mod session;
+
+pub use middleware::{basic_authentication, reject_anonymous_users, UserId};
+pub use password::{compute_password_hash, validate_credentials, AuthError, Credentials};
+pub use session::TypedSession;
@@ -0,0 +1,116 @@
+use crate::telemetry::spawn_blocking_with_tracing;
+use argon2::password_hash::SaltString;
+use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    /// 用户名不存在或密码错误，对外不区分两者
+    #[error("Invalid credentials.")]
+    InvalidCredentials,
+    #[error("Unexpected authentication error: {0}")]
+    Unexpected(String),
+}
+
+/// 校验用户名与密码，成功时返回 user_id
+/// 用户名不存在时仍对一个占位哈希做完整校验，使耗时与密码错误时一致，避免通过响应时间探测用户名
+#[tracing::instrument(name = "Validate credentials", skip(credentials, pool))]
+pub async fn validate_credentials(
+    credentials: Credentials,
+    pool: &PgPool,
+) -> Result<Uuid, AuthError> {
+    let mut user_id = None;
+    let mut expected_password_hash = "$argon2id$v=19$m=15000,t=2,p=1$\
+        gZiV/M1gPc22ElAH/Jh1Hw$\
+        CWOrkoo7oJBQ/iyh7uJ0LO2aLEfrHwTWllSAxT0zRno"
+        .to_string();
+
+    if let Some((stored_user_id, stored_password_hash)) =
+        get_stored_credentials(&credentials.username, pool).await?
+    {
+        user_id = Some(stored_user_id);
+        expected_password_hash = stored_password_hash;
+    }
+
+    spawn_blocking_with_tracing(move || {
+        verify_password_hash(expected_password_hash, credentials.password)
+    })
+        .await
+        .map_err(|e| AuthError::Unexpected(format!("Failed to spawn blocking task: {}", e)))??;
+
+    // 只有在用户存在时才可能走到这里
+    user_id.ok_or(AuthError::InvalidCredentials)
+}
+
+#[tracing::instrument(name = "Verify password hash", skip(expected_password_hash, password_candidate))]
+fn verify_password_hash(
+    expected_password_hash: String,
+    password_candidate: String,
+) -> Result<(), AuthError> {
+    let expected_password_hash = PasswordHash::new(&expected_password_hash)
+        .map_err(|e| AuthError::Unexpected(format!("Failed to parse hash in PHC string format: {}", e)))?;
+
+    Argon2::default()
+        .verify_password(password_candidate.as_bytes(), &expected_password_hash)
+        .map_err(|_| AuthError::InvalidCredentials)
+}
+
+#[tracing::instrument(name = "Get stored credentials", skip(username, pool))]
+async fn get_stored_credentials(
+    username: &str,
+    pool: &PgPool,
+) -> Result<Option<(Uuid, String)>, AuthError> {
+    let row = sqlx::query!(
+        r#"
+        SELECT user_id, password_hash
+        FROM users
+        WHERE username = $1
+        "#,
+        username,
+    )
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| AuthError::Unexpected(format!("Failed to retrieve stored credentials: {}", e)))?
+        .map(|row| (row.user_id, row.password_hash));
+    Ok(row)
+}
+
+/// 以 Argon2id（m=15000, t=2, p=1）计算密码的 PHC 字符串
+pub fn compute_password_hash(password: &str) -> Result<String, AuthError> {
+    let salt = SaltString::generate(&mut rand::thread_rng());
+    let password_hash = Argon2::new(
+        Algorithm::Argon2id,
+        Version::V0x13,
+        Params::new(15000, 2, 1, None)
+            .map_err(|e| AuthError::Unexpected(format!("Invalid Argon2 parameters: {}", e)))?,
+    )
+        .hash_password(password.as_bytes(), &salt)
+        .map_err(|e| AuthError::Unexpected(format!("Failed to hash password: {}", e)))?
+        .to_string();
+    Ok(password_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use claim::{assert_err, assert_ok};
+
+    #[test]
+    fn a_computed_hash_verifies_the_original_password() {
+        let hash = compute_password_hash("everythinghastostartsomewhere").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert_ok!(verify_password_hash(hash, "everythinghastostartsomewhere".into()));
+    }
+
+    #[test]
+    fn a_computed_hash_rejects_a_different_password() {
+        let hash = compute_password_hash("everythinghastostartsomewhere").unwrap();
+        assert_err!(verify_password_hash(hash, "wrong".into()));
+    }
+}
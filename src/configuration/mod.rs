@@ -0,0 +1,703 @@
+use crate::domain::{SubscriberEmail, ValidationPolicy};
+use crate::service::issue_delivery_worker::RetryPolicy;
+use secrecy::{ExposeSecret, SecretString};
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions, PgSslMode};
+use std::path::{Path, PathBuf};
+
+mod validation;
+
+pub use validation::{ConfigurationProblem, ValidationReport};
+
+/// 应用整体配置项
+/// 从配置文件与环境变量反序列化而来
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct Settings {
+    pub database: DatabaseSettings,       // 数据库配置
+    pub application: ApplicationSettings, // 应用运行时配置（主机/端口等）
+    pub email: EmailClientSettings,       // 邮件客户端配置
+    pub delivery: DeliverySettings,       // 投递队列重试配置
+    pub idempotency: IdempotencySettings, // 幂等键过期配置
+    pub validation: ValidationSettings,   // 订阅字段校验规则
+    pub suppression: SuppressionSettings, // 订阅与投递的拦截配置
+    pub health: HealthSettings,           // 就绪检查配置
+}
+
+/// 导出配置时以占位符代替 secret，避免 `--print-config` 之类的输出泄露密钥
+fn serialize_redacted<S: serde::Serializer>(_: &SecretString, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str("[REDACTED]")
+}
+
+/// `/health/ready` 的检查配置
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct HealthSettings {
+    pub check_timeout_milliseconds: u64, // 单项检查的超时，超时视为失败
+    pub pool_saturation_threshold: f64,  // 连接池占用比例达到该值时报告 warn
+    pub check_email_backend: bool,       // 是否检查邮件后端的连通性（如 SMTP 握手）
+}
+
+impl HealthSettings {
+    pub fn check_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.check_timeout_milliseconds)
+    }
+}
+
+/// 订阅与投递的拦截配置
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct SuppressionSettings {
+    pub disposable_domains_file: String, // 一次性邮箱域名列表，相对路径以配置目录为基准
+}
+
+/// 订阅字段的校验规则
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct ValidationSettings {
+    pub max_name_length: usize,             // 名称的最大长度（按字素簇计）
+    pub forbidden_characters: String,       // 名称中禁止出现的字符，逐字符生效
+    pub allowed_email_domains: Vec<String>, // 允许的邮箱域名（含子域名），为空表示不限制
+    pub blocked_email_domains: Vec<String>, // 禁止的邮箱域名（含子域名）
+    pub allow_plus_addressing: bool,        // 是否接受 `user+tag@domain` 形式的地址
+}
+
+impl ValidationSettings {
+    /// 域名与订阅地址使用相同的规范化方式（小写 ASCII / punycode），无法转换的域名原样小写保留
+    pub fn policy(&self) -> ValidationPolicy {
+        let normalize_domains = |domains: &[String]| {
+            domains
+                .iter()
+                .map(|domain| {
+                    let domain = domain.trim();
+                    idna::domain_to_ascii(domain).unwrap_or_else(|_| domain.to_lowercase())
+                })
+                .collect()
+        };
+        ValidationPolicy {
+            max_name_length: self.max_name_length,
+            forbidden_characters: self.forbidden_characters.chars().collect(),
+            allowed_email_domains: normalize_domains(&self.allowed_email_domains),
+            blocked_email_domains: normalize_domains(&self.blocked_email_domains),
+            allow_plus_addressing: self.allow_plus_addressing,
+        }
+    }
+}
+
+/// 幂等键的保留与清理配置
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct IdempotencySettings {
+    pub ttl_hours: u64,              // 幂等键保留时长
+    pub sweep_interval_seconds: u64, // 过期清理任务的执行间隔
+}
+
+impl IdempotencySettings {
+    pub fn ttl(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.ttl_hours * 60 * 60)
+    }
+
+    pub fn sweep_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.sweep_interval_seconds)
+    }
+}
+
+/// 投递队列的重试配置
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct DeliverySettings {
+    pub max_attempts: u32,         // 含首次投递在内的最大尝试次数
+    pub backoff_base_seconds: u64, // 第一次重试前的基础等待
+    pub backoff_max_seconds: u64,  // 单次等待的上限
+}
+
+impl DeliverySettings {
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: self.max_attempts,
+            base_delay: std::time::Duration::from_secs(self.backoff_base_seconds),
+            max_delay: std::time::Duration::from_secs(self.backoff_max_seconds),
+        }
+    }
+}
+
+
+/// 邮件客户端配置
+/// `kind` 选择发送后端，其余字段按后端取用
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct EmailClientSettings {
+    pub kind: EmailClientKind,
+    pub sender_email: String,
+    // smtp
+    pub smtp_host: String,
+    pub smtp_port: u16,
+    pub smtp_username: String,
+    #[serde(serialize_with = "serialize_redacted")]
+    pub smtp_password: SecretString,
+    pub use_starttls: bool,
+    pub smtp_pool_max_connections: u32,      // 最大并发 SMTP 连接数
+    pub smtp_pool_idle_timeout_seconds: u64, // 空闲连接保留时长
+    // http
+    pub api_base_url: String,
+    #[serde(serialize_with = "serialize_redacted")]
+    pub api_token: SecretString,
+    // smtp / http 共用：单次请求超时
+    pub timeout_milliseconds: u64,
+    // file
+    pub output_dir: String,
+}
+
+/// 邮件发送后端类型
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmailClientKind {
+    Smtp, // SMTP 中继
+    Http, // JSON HTTP API（Postmark 风格）
+    File, // 写入本地 .eml 文件
+}
+
+impl EmailClientSettings {
+    pub fn sender(&self) -> Result<SubscriberEmail, String> {
+        SubscriberEmail::parse(self.sender_email.clone()).map_err(|e| e.to_string())
+    }
+
+    pub fn timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.timeout_milliseconds)
+    }
+
+    pub fn smtp_pool_idle_timeout(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.smtp_pool_idle_timeout_seconds)
+    }
+}
+
+/// 数据库连接配置
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct DatabaseSettings {
+    pub username: String,                    // 数据库用户名
+    #[serde(serialize_with = "serialize_redacted")]
+    pub password: SecretString,              // 数据库密码
+    pub port: u16,                           // 数据库端口
+    pub host: String,                        // 数据库主机名/IP
+    pub database_name: String,               // 数据库名称
+    pub ssl_mode: DatabaseSslMode,           // TLS 模式，对应 libpq 的 sslmode
+    pub ssl_root_cert: Option<String>,       // 校验服务端证书所用的 CA 证书路径
+    pub application_name: String,            // 在 pg_stat_activity 中显示的应用名
+    pub statement_timeout_milliseconds: u64, // 单条语句的超时，0 表示不限制
+    pub pool_max_connections: u32,           // 连接池最大连接数
+    pub pool_min_connections: u32,           // 连接池保持的最少连接数
+    pub pool_acquire_timeout_seconds: u64,   // 从连接池获取连接的超时
+    pub pool_idle_timeout_seconds: u64,      // 空闲连接保留时长
+}
+
+/// 数据库 TLS 模式，取值与 libpq 的 `sslmode` 相同
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum DatabaseSslMode {
+    Disable,    // 不使用 TLS
+    Allow,      // 服务端要求时才使用 TLS
+    Prefer,     // 优先使用 TLS，失败时退回明文
+    Require,    // 必须使用 TLS，不校验证书
+    VerifyCa,   // 必须使用 TLS，并校验证书由受信任的 CA 签发
+    VerifyFull, // 在 verify-ca 的基础上校验主机名
+}
+
+impl From<DatabaseSslMode> for PgSslMode {
+    fn from(mode: DatabaseSslMode) -> Self {
+        match mode {
+            DatabaseSslMode::Disable => PgSslMode::Disable,
+            DatabaseSslMode::Allow => PgSslMode::Allow,
+            DatabaseSslMode::Prefer => PgSslMode::Prefer,
+            DatabaseSslMode::Require => PgSslMode::Require,
+            DatabaseSslMode::VerifyCa => PgSslMode::VerifyCa,
+            DatabaseSslMode::VerifyFull => PgSslMode::VerifyFull,
+        }
+    }
+}
+
+/// 应用层配置（Host/Port）
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
+pub struct ApplicationSettings {
+    pub port: u16,                 // HTTP 监听端口
+    pub host: String,              // 监听地址（如 127.0.0.1 或 0.0.0.0）
+    pub base_url: String,          // 对外访问地址，用于拼接邮件中的链接
+    #[serde(serialize_with = "serialize_redacted")]
+    pub hmac_secret: SecretString, // 会话与 flash 消息 cookie 的签名密钥，至少 64 字节
+}
+
+impl DatabaseSettings {
+    /// 不指定数据库的连接参数（用于建库等场景）
+    /// 各字段分别传入而不是拼接 URL，密码中的 `@`、`/` 等字符无需转义
+    pub fn connect_options_without_db(&self) -> PgConnectOptions {
+        let mut options = PgConnectOptions::new()
+            .host(&self.host)
+            .port(self.port)
+            .username(&self.username)
+            .password(self.password.expose_secret())
+            .ssl_mode(self.ssl_mode.into())
+            .application_name(&self.application_name);
+        if let Some(ssl_root_cert) = &self.ssl_root_cert {
+            options = options.ssl_root_cert(ssl_root_cert);
+        }
+        if self.statement_timeout_milliseconds > 0 {
+            options = options.options([("statement_timeout", self.statement_timeout_milliseconds.to_string())]);
+        }
+        options
+    }
+
+    /// 完整的连接参数（包含数据库名）
+    pub fn connect_options(&self) -> PgConnectOptions {
+        self.connect_options_without_db().database(&self.database_name)
+    }
+
+    pub fn pool_options(&self) -> PgPoolOptions {
+        PgPoolOptions::new()
+            .max_connections(self.pool_max_connections)
+            .min_connections(self.pool_min_connections)
+            .acquire_timeout(std::time::Duration::from_secs(self.pool_acquire_timeout_seconds))
+            .idle_timeout(std::time::Duration::from_secs(self.pool_idle_timeout_seconds))
+    }
+}
+
+/// 读取配置失败的原因
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigurationError {
+    #[error("Failed to determine the current directory.")]
+    CurrentDirectory(#[source] std::io::Error),
+    #[error("{0}")]
+    Environment(String),
+    #[error("Found neither {name}.yaml, {name}.yml, {name}.toml nor {name}.json in {}.", directory.display())]
+    MissingLayer { name: String, directory: PathBuf },
+    #[error("Failed to load configuration from {}.", directory.display())]
+    Load {
+        directory: PathBuf,
+        #[source]
+        source: config::ConfigError,
+    },
+    #[error(transparent)]
+    Invalid(#[from] ValidationReport),
+}
+
+/// 配置来源：配置目录、运行环境与命令行覆盖项
+#[derive(Debug, Clone)]
+pub struct ConfigurationSource {
+    pub directory: PathBuf,               // 配置文件目录
+    pub environment: Option<String>,      // 运行环境，为空时读取 APP_ENVIRONMENT，默认 local
+    pub overrides: Vec<(String, String)>, // 命令行中的 `--set key=value`，优先级最高
+}
+
+impl ConfigurationSource {
+    /// 当前目录下的 `configuration` 目录
+    pub fn current_dir() -> Result<Self, ConfigurationError> {
+        let base_path = std::env::current_dir().map_err(ConfigurationError::CurrentDirectory)?;
+        Ok(Self {
+            directory: base_path.join("configuration"),
+            environment: None,
+            overrides: Vec::new(),
+        })
+    }
+}
+
+/// 从当前目录下的 `configuration` 目录读取配置
+pub fn get_configuration() -> Result<Settings, ConfigurationError> {
+    load_configuration(&ConfigurationSource::current_dir()?)
+}
+
+/// 读取、合并并校验配置，合并规则见 `merge_configuration`
+pub fn load_configuration(source: &ConfigurationSource) -> Result<Settings, ConfigurationError> {
+    let settings = merge_configuration(source)?;
+    settings.validate()?;
+    Ok(settings)
+}
+
+/// 读取并合并配置但不校验，供 `--print-config` 在配置有误时也能输出合并结果
+/// 后面的层覆盖前面的层：
+/// 1) base.{yaml,yml,toml,json} 基础配置
+/// 2) {ENV}.{yaml,yml,toml,json} 环境配置（ENV 来自 `environment` 或 APP_ENVIRONMENT，默认 local）
+/// 3) 环境变量覆盖（前缀 `APP__`，层级分隔符 `__`）
+///    例如：`APP__APPLICATION__HOST=0.0.0.0` 会覆盖 `application.host`
+///    旧的 `APP_APPLICATION__HOST` 写法仍然生效，但会记录弃用警告
+/// 4) `_FILE` 结尾的环境变量从文件读取对应配置项，用于 Docker/Kubernetes secrets
+///    例如：`APP__DATABASE__PASSWORD_FILE=/run/secrets/db_password` 会覆盖 `database.password`
+/// 5) 命令行覆盖项，例如 `--set email.smtp_port=587`
+///
+/// 同一层中存在多种格式的文件时按 yaml、yml、toml、json 的顺序合并；
+/// 配置中的相对文件路径以配置目录为基准
+pub fn merge_configuration(source: &ConfigurationSource) -> Result<Settings, ConfigurationError> {
+    merge_layers(source, std::env::vars().collect())
+}
+
+/// `merge_configuration` 的实现，环境变量由调用方传入以便测试
+fn merge_layers(source: &ConfigurationSource, vars: Vec<(String, String)>) -> Result<Settings, ConfigurationError> {
+    const ENV_KEY: &str = "APP_ENVIRONMENT";
+
+    let directory = &source.directory;
+    let environment: Environment = match &source.environment {
+        Some(environment) => environment.clone(),
+        // 侦测运行环境，默认 local
+        None => vars
+            .iter()
+            .find(|(name, _)| name == ENV_KEY)
+            .map_or_else(|| "local".into(), |(_, value)| value.clone()),
+    }
+        .try_into()
+        .map_err(ConfigurationError::Environment)?;
+    let load_error = |source| ConfigurationError::Load {
+        directory: directory.to_owned(),
+        source,
+    };
+
+    let mut builder = config::Config::builder();
+    for layer in ["base", environment.as_str()] {
+        let files = layer_files(directory, layer);
+        if files.is_empty() {
+            return Err(ConfigurationError::MissingLayer {
+                name: layer.to_owned(),
+                directory: directory.to_owned(),
+            });
+        }
+        for file in files {
+            builder = builder.add_source(config::File::from(file));
+        }
+    }
+    // 环境变量覆盖
+    builder = builder.add_source(
+        config::Environment::with_prefix("APP")
+            .prefix_separator("__")
+            .separator("__")
+            .source(Some(environment_variables(vars.iter().cloned()))),
+    );
+    for (key, path) in secret_file_overrides(vars.into_iter()) {
+        let value = std::fs::read_to_string(&path).map_err(|e| {
+            load_error(config::ConfigError::Message(format!("Failed to read `{}` from {}: {}", key, path, e)))
+        })?;
+        // secret 文件通常以换行结尾
+        builder = builder
+            .set_override(key, value.trim_end_matches(['\r', '\n']))
+            .map_err(load_error)?;
+    }
+    for (key, value) in &source.overrides {
+        builder = builder.set_override(key.as_str(), value.as_str()).map_err(load_error)?;
+    }
+
+    let mut settings: Settings = builder
+        .build()
+        .and_then(|config| config.try_deserialize())
+        .map_err(load_error)?;
+    settings.suppression.disposable_domains_file = directory
+        .join(&settings.suppression.disposable_domains_file)
+        .to_string_lossy()
+        .into_owned();
+    Ok(settings)
+}
+
+/// 某一层存在的配置文件
+fn layer_files(directory: &Path, name: &str) -> Vec<PathBuf> {
+    ["yaml", "yml", "toml", "json"]
+        .iter()
+        .map(|extension| directory.join(format!("{}.{}", name, extension)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// 取出 `APP__` 前缀的环境变量
+/// 旧版本只识别 `APP_A__B` 形式，这类变量改写为 `APP__A__B` 后继续生效并记录弃用警告；两种写法同时存在时以新写法为准
+fn environment_variables(vars: impl Iterator<Item = (String, String)>) -> config::Map<String, String> {
+    let mut variables = config::Map::new();
+    let mut deprecated = Vec::new();
+    for (name, value) in vars {
+        if name.starts_with("APP__") {
+            variables.insert(name, value);
+        } else if let Some(key) = name.strip_prefix("APP_").filter(|key| key.contains("__")) {
+            deprecated.push((format!("APP__{}", key), name, value));
+        }
+    }
+    for (renamed, name, value) in deprecated {
+        tracing::warn!("`{}` uses the deprecated `APP_` prefix, rename it to `{}`", name, renamed);
+        variables.entry(renamed).or_insert(value);
+    }
+    variables
+}
+
+/// 从 `APP__A__B_FILE=path` 形式的环境变量中取出 `(a.b, path)`
+fn secret_file_overrides(vars: impl Iterator<Item = (String, String)>) -> Vec<(String, String)> {
+    vars.filter_map(|(name, path)| {
+        let key = name.strip_prefix("APP__")?.strip_suffix("_FILE")?;
+        let key = key.split("__").map(str::to_lowercase).collect::<Vec<_>>().join(".");
+        Some((key, path))
+    })
+        .collect()
+}
+
+/// 运行环境，决定加载哪个环境配置文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Environment {
+    Local,
+    Staging,
+    Test,
+    Production,
+    Custom(String), // 其他环境名，对应同名的配置文件
+}
+
+impl Environment {
+    /// 以字符串形式返回环境名（用于拼接文件名等）
+    pub fn as_str(&self) -> &str {
+        match self {
+            Environment::Local => "local",
+            Environment::Staging => "staging",
+            Environment::Test => "test",
+            Environment::Production => "production",
+            Environment::Custom(name) => name,
+        }
+    }
+}
+
+impl TryFrom<String> for Environment {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        // 仅有 ASCII 名称，使用 to_ascii_lowercase 更高效
+        let s = s.to_ascii_lowercase();
+        match s.as_str() {
+            "local" => Ok(Self::Local),
+            "staging" => Ok(Self::Staging),
+            "test" => Ok(Self::Test),
+            "production" => Ok(Self::Production),
+            // 环境名用于拼接文件名，只允许字母、数字、`-` 与 `_`
+            other if !other.is_empty()
+                && other != "base"
+                && other.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                Ok(Self::Custom(s))
+            }
+            other => Err(format!(
+                "`{}` is not a valid environment name. Use `local`, `staging`, `test`, `production` \
+                or a custom name made of letters, digits, `-` and `_`.",
+                other
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        environment_variables, layer_files, merge_layers, secret_file_overrides,
+        ConfigurationSource, DatabaseSettings, DatabaseSslMode, Environment, Settings,
+    };
+    use secrecy::{ExposeSecret, SecretString};
+    use sqlx::postgres::PgSslMode;
+    use std::path::{Path, PathBuf};
+    use uuid::Uuid;
+
+    /// 复制仓库自带配置的临时目录，测试不依赖当前工作目录；离开作用域时删除
+    pub(super) struct ScratchDirectory(PathBuf);
+
+    impl ScratchDirectory {
+        pub(super) fn new() -> Self {
+            let bundled = Path::new(env!("CARGO_MANIFEST_DIR")).join("configuration");
+            let directory = std::env::temp_dir().join(Uuid::new_v4().to_string());
+            std::fs::create_dir_all(&directory).unwrap();
+            for file in ["base.yaml", "local.yaml", "test.yaml", "disposable_domains.txt"] {
+                std::fs::copy(bundled.join(file), directory.join(file)).unwrap();
+            }
+            Self(directory)
+        }
+
+        pub(super) fn path(&self) -> &Path {
+            &self.0
+        }
+
+        pub(super) fn source(&self, environment: &str) -> ConfigurationSource {
+            ConfigurationSource {
+                directory: self.0.clone(),
+                environment: Some(environment.into()),
+                overrides: Vec::new(),
+            }
+        }
+
+        /// 合并指定环境的配置，只使用传入的环境变量
+        pub(super) fn settings(&self, environment: &str, vars: &[(&str, &str)]) -> Settings {
+            merge_layers(&self.source(environment), to_vars(vars)).unwrap()
+        }
+    }
+
+    impl Drop for ScratchDirectory {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn to_vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect()
+    }
+
+    fn database_settings() -> DatabaseSettings {
+        DatabaseSettings {
+            username: "progcxw".into(),
+            password: SecretString::from("p@ss/word:with?special#chars"),
+            port: 5433,
+            host: "db.internal".into(),
+            database_name: "newsletter".into(),
+            ssl_mode: DatabaseSslMode::VerifyFull,
+            ssl_root_cert: None,
+            application_name: "actix-try".into(),
+            statement_timeout_milliseconds: 5000,
+            pool_max_connections: 10,
+            pool_min_connections: 0,
+            pool_acquire_timeout_seconds: 5,
+            pool_idle_timeout_seconds: 600,
+        }
+    }
+
+    #[test]
+    fn connect_options_are_built_field_by_field() {
+        let options = database_settings().connect_options();
+
+        assert_eq!(options.get_host(), "db.internal");
+        assert_eq!(options.get_port(), 5433);
+        assert_eq!(options.get_database(), Some("newsletter"));
+        assert!(matches!(options.get_ssl_mode(), PgSslMode::VerifyFull));
+        assert_eq!(options.get_application_name(), Some("actix-try"));
+        assert_eq!(options.get_options(), Some("-c statement_timeout=5000"));
+    }
+
+    #[test]
+    fn secrets_are_redacted_in_debug_output() {
+        let output = format!("{:?}", database_settings());
+
+        assert!(!output.contains("p@ss/word"));
+        assert!(output.contains("REDACTED"));
+    }
+
+    #[test]
+    fn file_variables_are_mapped_to_setting_keys() {
+        let vars = vec![
+            ("APP__DATABASE__PASSWORD_FILE".to_string(), "/run/secrets/db".to_string()),
+            ("APP__EMAIL__API_TOKEN_FILE".to_string(), "/run/secrets/token".to_string()),
+            ("APP__DATABASE__PASSWORD".to_string(), "ignored".to_string()),
+            ("HOME_FILE".to_string(), "ignored".to_string()),
+        ];
+
+        assert_eq!(
+            secret_file_overrides(vars.into_iter()),
+            vec![
+                ("database.password".to_string(), "/run/secrets/db".to_string()),
+                ("email.api_token".to_string(), "/run/secrets/token".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn a_zero_statement_timeout_is_not_sent() {
+        let settings = DatabaseSettings {
+            statement_timeout_milliseconds: 0,
+            ..database_settings()
+        };
+
+        assert_eq!(settings.connect_options_without_db().get_options(), None);
+    }
+
+    #[test]
+    fn known_and_custom_environments_are_parsed() {
+        assert_eq!(Environment::try_from("Staging".to_string()), Ok(Environment::Staging));
+        assert_eq!(Environment::try_from("test".to_string()), Ok(Environment::Test));
+        assert_eq!(
+            Environment::try_from("eu-west_2".to_string()),
+            Ok(Environment::Custom("eu-west_2".into()))
+        );
+        for name in ["", "base", "../secrets", "qa.eu"] {
+            assert!(Environment::try_from(name.to_string()).is_err(), "{} should be rejected", name);
+        }
+    }
+
+    #[test]
+    fn toml_and_json_layers_are_merged_after_yaml() {
+        let scratch = ScratchDirectory::new();
+        let directory = scratch.path();
+        let toml = r#"
+[application]
+host = "0.0.0.0"
+port = 9000
+base_url = "http://qa.example"
+"#;
+        std::fs::write(directory.join("qa.toml"), toml).unwrap();
+        std::fs::write(directory.join("qa.json"), r#"{"application": {"port": 9001}}"#).unwrap();
+
+        let files = layer_files(directory, "qa");
+        let settings = merge_layers(&scratch.source("qa"), Vec::new()).unwrap();
+
+        assert_eq!(files, vec![directory.join("qa.toml"), directory.join("qa.json")]);
+        assert_eq!(settings.application.host, "0.0.0.0");
+        assert_eq!(settings.application.port, 9001);
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn a_missing_environment_file_is_reported() {
+        let scratch = ScratchDirectory::new();
+
+        let error = merge_layers(&scratch.source("does-not-exist"), Vec::new()).unwrap_err();
+
+        assert!(error.to_string().starts_with("Found neither does-not-exist.yaml"));
+    }
+
+    #[test]
+    fn environment_variables_override_the_configuration_files() {
+        let settings = ScratchDirectory::new().settings("test", &[("APP__APPLICATION__PORT", "8001")]);
+
+        assert_eq!(settings.application.port, 8001);
+    }
+
+    #[test]
+    fn command_line_overrides_take_precedence_over_environment_variables() {
+        let scratch = ScratchDirectory::new();
+        let source = ConfigurationSource {
+            overrides: vec![("application.port".into(), "8123".into())],
+            ..scratch.source("test")
+        };
+
+        let settings = merge_layers(&source, to_vars(&[("APP__APPLICATION__PORT", "8001")])).unwrap();
+
+        assert_eq!(settings.application.port, 8123);
+    }
+
+    #[test]
+    fn deprecated_single_underscore_variables_still_apply() {
+        let settings = ScratchDirectory::new().settings("test", &[("APP_APPLICATION__HOST", "0.0.0.0")]);
+
+        assert_eq!(settings.application.host, "0.0.0.0");
+    }
+
+    #[test]
+    fn the_double_underscore_prefix_wins_over_the_deprecated_one() {
+        let variables = environment_variables(
+            to_vars(&[
+                ("APP_APPLICATION__PORT", "8001"),
+                ("APP__APPLICATION__PORT", "8002"),
+                ("APP_ENVIRONMENT", "test"),
+                ("PATH", "/usr/bin"),
+            ])
+            .into_iter(),
+        );
+
+        assert_eq!(variables.len(), 1);
+        assert_eq!(variables["APP__APPLICATION__PORT"], "8002");
+    }
+
+    #[test]
+    fn invalid_configurations_can_still_be_merged() {
+        let scratch = ScratchDirectory::new();
+        let source = ConfigurationSource {
+            overrides: vec![("application.host".into(), "not a host".into())],
+            ..scratch.source("test")
+        };
+
+        let settings = merge_layers(&source, Vec::new()).unwrap();
+
+        assert_eq!(settings.application.host, "not a host");
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn secrets_are_redacted_in_the_serialized_configuration() {
+        let settings = ScratchDirectory::new().settings("test", &[]);
+
+        let output = serde_json::to_string(&settings).unwrap();
+
+        assert!(!output.contains(settings.application.hmac_secret.expose_secret()));
+        assert!(output.contains(r#""hmac_secret":"[REDACTED]""#));
+    }
+}
@@ -0,0 +1,262 @@
+use super::{EmailClientKind, Settings};
+use secrecy::ExposeSecret;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// 单个配置项的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigurationProblem {
+    pub key: &'static str, // 出问题的配置项，如 `email.smtp_port`
+    pub message: String,   // 问题描述与修改建议
+}
+
+/// 配置校验结果，包含所有未通过校验的配置项
+#[derive(Debug, Default, thiserror::Error)]
+pub struct ValidationReport {
+    pub problems: Vec<ConfigurationProblem>,
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Found {} configuration problem(s):", self.problems.len())?;
+        for problem in &self.problems {
+            write!(f, "\n  - {}: {}", problem.key, problem.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl ValidationReport {
+    /// `valid` 为 `false` 时记录一条问题
+    fn check(&mut self, valid: bool, key: &'static str, message: impl FnOnce() -> String) {
+        if !valid {
+            self.problems.push(ConfigurationProblem { key, message: message() });
+        }
+    }
+}
+
+impl Settings {
+    /// 检查所有配置项后再返回，而不是遇到第一个问题就退出
+    pub fn validate(&self) -> Result<(), ValidationReport> {
+        let mut report = ValidationReport::default();
+
+        let application = &self.application;
+        report.check(is_valid_host(&application.host), "application.host", || {
+            format!("`{}` is not a valid IP address or hostname.", application.host)
+        });
+        report.check(is_valid_http_url(&application.base_url), "application.base_url", || {
+            format!("`{}` is not an absolute http(s) URL.", application.base_url)
+        });
+        report.check(application.hmac_secret.expose_secret().len() >= 64, "application.hmac_secret", || {
+            "The secret must be at least 64 bytes long.".into()
+        });
+
+        let database = &self.database;
+        report.check(is_valid_host(&database.host), "database.host", || {
+            format!("`{}` is not a valid IP address or hostname.", database.host)
+        });
+        report.check(database.port != 0, "database.port", || "The port cannot be 0.".into());
+        report.check(!database.database_name.is_empty(), "database.database_name", || {
+            "The database name cannot be empty.".into()
+        });
+        report.check(database.pool_max_connections > 0, "database.pool_max_connections", || {
+            "The pool needs at least one connection.".into()
+        });
+        report.check(
+            database.pool_min_connections <= database.pool_max_connections,
+            "database.pool_min_connections",
+            || {
+                format!(
+                    "{} is larger than pool_max_connections ({}).",
+                    database.pool_min_connections, database.pool_max_connections
+                )
+            },
+        );
+        if let Some(ssl_root_cert) = &database.ssl_root_cert {
+            report.check(Path::new(ssl_root_cert).is_file(), "database.ssl_root_cert", || {
+                format!("`{}` does not exist.", ssl_root_cert)
+            });
+        }
+
+        let email = &self.email;
+        if let Err(e) = email.sender() {
+            report.problems.push(ConfigurationProblem { key: "email.sender_email", message: e });
+        }
+        report.check(email.timeout_milliseconds > 0, "email.timeout_milliseconds", || {
+            "The timeout cannot be 0.".into()
+        });
+        match email.kind {
+            EmailClientKind::Smtp => {
+                report.check(is_valid_host(&email.smtp_host), "email.smtp_host", || {
+                    format!("`{}` is not a valid IP address or hostname.", email.smtp_host)
+                });
+                report.check(email.smtp_port != 0, "email.smtp_port", || "The port cannot be 0.".into());
+                // 465 为隐式 TLS（SMTPS），不能再升级为 STARTTLS
+                report.check(email.smtp_port != 465 || !email.use_starttls, "email.use_starttls", || {
+                    "Port 465 uses implicit TLS. Set `use_starttls: false` or use port 587.".into()
+                });
+                // 25/587 通常使用 STARTTLS，但本地中继或 MTA sidecar 也可能另有配置，只记录警告
+                if !email.use_starttls && matches!(email.smtp_port, 25 | 587) {
+                    tracing::warn!(
+                        "email.smtp_port is {} but use_starttls is false, so implicit TLS will be used. \
+                        Most servers expect STARTTLS on this port.",
+                        email.smtp_port
+                    );
+                }
+                report.check(email.smtp_pool_max_connections > 0, "email.smtp_pool_max_connections", || {
+                    "The pool needs at least one connection.".into()
+                });
+            }
+            EmailClientKind::Http => {
+                report.check(is_valid_http_url(&email.api_base_url), "email.api_base_url", || {
+                    format!("`{}` is not an absolute http(s) URL.", email.api_base_url)
+                });
+                report.check(!email.api_token.expose_secret().is_empty(), "email.api_token", || {
+                    "The HTTP backend needs an API token.".into()
+                });
+            }
+            EmailClientKind::File => {
+                report.check(!email.output_dir.is_empty(), "email.output_dir", || {
+                    "The output directory cannot be empty.".into()
+                });
+            }
+        }
+
+        let delivery = &self.delivery;
+        report.check(delivery.max_attempts > 0, "delivery.max_attempts", || {
+            "At least one delivery attempt is required.".into()
+        });
+        report.check(
+            delivery.backoff_base_seconds <= delivery.backoff_max_seconds,
+            "delivery.backoff_base_seconds",
+            || {
+                format!(
+                    "{} is larger than backoff_max_seconds ({}).",
+                    delivery.backoff_base_seconds, delivery.backoff_max_seconds
+                )
+            },
+        );
+
+        let idempotency = &self.idempotency;
+        report.check(idempotency.ttl_hours > 0, "idempotency.ttl_hours", || "The TTL cannot be 0.".into());
+        report.check(idempotency.sweep_interval_seconds > 0, "idempotency.sweep_interval_seconds", || {
+            "The sweep interval cannot be 0.".into()
+        });
+
+        let validation = &self.validation;
+        report.check(validation.max_name_length > 0, "validation.max_name_length", || {
+            "The maximum name length cannot be 0.".into()
+        });
+        for domain in &validation.allowed_email_domains {
+            report.check(is_valid_host(domain), "validation.allowed_email_domains", || {
+                format!("`{}` is not a valid domain.", domain)
+            });
+        }
+        for domain in &validation.blocked_email_domains {
+            report.check(is_valid_host(domain), "validation.blocked_email_domains", || {
+                format!("`{}` is not a valid domain.", domain)
+            });
+        }
+
+        let disposable_domains_file = &self.suppression.disposable_domains_file;
+        report.check(Path::new(disposable_domains_file).is_file(), "suppression.disposable_domains_file", || {
+            format!("`{}` does not exist.", disposable_domains_file)
+        });
+
+        let health = &self.health;
+        report.check(health.check_timeout_milliseconds > 0, "health.check_timeout_milliseconds", || {
+            "The timeout cannot be 0.".into()
+        });
+        report.check(
+            health.pool_saturation_threshold > 0.0 && health.pool_saturation_threshold <= 1.0,
+            "health.pool_saturation_threshold",
+            || format!("{} is not a ratio in (0, 1].", health.pool_saturation_threshold),
+        );
+
+        if report.problems.is_empty() {
+            Ok(())
+        } else {
+            Err(report)
+        }
+    }
+}
+
+/// IP 地址，或由字母、数字与 `-` 组成的域名（国际化域名先转换为 punycode）
+fn is_valid_host(host: &str) -> bool {
+    if host.parse::<IpAddr>().is_ok() {
+        return true;
+    }
+    let Ok(host) = idna::domain_to_ascii(host) else {
+        return false;
+    };
+    !host.is_empty()
+        && host.len() <= 253
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+fn is_valid_http_url(url: &str) -> bool {
+    reqwest::Url::parse(url).is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_valid_host;
+    use crate::configuration::tests::ScratchDirectory;
+    use crate::configuration::EmailClientKind;
+
+    #[test]
+    fn the_bundled_configuration_is_valid() {
+        let scratch = ScratchDirectory::new();
+
+        for environment in ["local", "test"] {
+            let settings = scratch.settings(environment, &[]);
+            assert!(settings.validate().is_ok(), "{} should be valid", environment);
+        }
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let scratch = ScratchDirectory::new();
+        let mut settings = scratch.settings("local", &[]);
+        settings.application.host = "not a host".into();
+        settings.email.kind = EmailClientKind::Smtp;
+        settings.email.smtp_port = 465;
+        settings.email.use_starttls = true;
+        settings.database.pool_min_connections = settings.database.pool_max_connections + 1;
+
+        let report = settings.validate().unwrap_err();
+
+        let keys: Vec<_> = report.problems.iter().map(|p| p.key).collect();
+        assert_eq!(keys, vec!["application.host", "database.pool_min_connections", "email.use_starttls"]);
+        assert!(report.to_string().starts_with("Found 3 configuration problem(s):"));
+    }
+
+    #[test]
+    fn smtp_ports_25_and_587_without_starttls_are_accepted() {
+        let scratch = ScratchDirectory::new();
+        let mut settings = scratch.settings("local", &[]);
+        settings.email.kind = EmailClientKind::Smtp;
+        settings.email.use_starttls = false;
+
+        for port in [25, 587] {
+            settings.email.smtp_port = port;
+            assert!(settings.validate().is_ok(), "port {} should be accepted", port);
+        }
+    }
+
+    #[test]
+    fn hosts_are_ip_addresses_or_domain_names() {
+        for host in ["127.0.0.1", "::1", "localhost", "smtp.163.com", "bücher.example"] {
+            assert!(is_valid_host(host), "{} should be valid", host);
+        }
+        for host in ["", "not a host", "-leading.example", "double..dot", "under_score.example"] {
+            assert!(!is_valid_host(host), "{} should be invalid", host);
+        }
+    }
+}
@@ -1,7 +1,11 @@
 pub mod subscriber_name;
 pub mod subscriber_email;
 pub mod new_subscriber;
+pub mod unsubscribe_token;
+pub mod validation_policy;
 
 pub use subscriber_name::*;
 pub use subscriber_email::*;
-pub use new_subscriber::*;
\ No newline at end of file
+pub use new_subscriber::*;
+pub use unsubscribe_token::*;
+pub use validation_policy::*;
\ No newline at end of file
@@ -1,5 +1,5 @@
 use crate::domain::subscriber_email::SubscriberEmail;
-use crate::domain::SubscriberName;
+use crate::domain::{SubscriberName, ValidationPolicy};
 use crate::routes::FormData;
 
 pub struct NewSubscriber {
@@ -7,13 +7,83 @@ pub struct NewSubscriber {
     pub name: SubscriberName,
 }
 
+/// 单个字段的校验失败
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub code: &'static str, // empty / too_long / forbidden_character / invalid_format / domain_not_allowed / plus_addressing / disposable_domain / blocked
+    pub message: String,
+}
+
+/// 订阅表单的校验结果，包含所有未通过校验的字段
+#[derive(Debug, thiserror::Error)]
+pub struct ValidationError {
+    pub errors: Vec<FieldError>,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let messages: Vec<_> = self.errors.iter().map(|e| e.message.as_str()).collect();
+        write!(f, "{}", messages.join(" "))
+    }
+}
+
 impl TryFrom<FormData> for NewSubscriber {
-    type Error = String;
+    type Error = ValidationError;
 
     fn try_from(form: FormData) -> Result<Self, Self::Error> {
-        let email = SubscriberEmail::parse(form.email)?;
-        let name = SubscriberName::parse(form.name)?;
-        Ok(Self { email, name })
+        Self::parse(form, &ValidationPolicy::default())
+    }
+}
+
+impl NewSubscriber {
+    /// 按部署配置的规则校验全部字段后再返回，而不是遇到第一个错误就停止
+    pub fn parse(form: FormData, policy: &ValidationPolicy) -> Result<Self, ValidationError> {
+        let mut errors = Vec::new();
+        let email = SubscriberEmail::parse_with(form.email, policy).map_err(|e| FieldError {
+            field: "email",
+            code: e.code(),
+            message: e.to_string(),
+        });
+        let name = SubscriberName::parse_with(form.name, policy).map_err(|e| FieldError {
+            field: "name",
+            code: e.code(),
+            message: e.to_string(),
+        });
+        errors.extend(email.as_ref().err().cloned());
+        errors.extend(name.as_ref().err().cloned());
+        match (email, name) {
+            (Ok(email), Ok(name)) => Ok(Self { email, name }),
+            _ => Err(ValidationError { errors }),
+        }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::NewSubscriber;
+    use crate::routes::FormData;
+
+    #[test]
+    fn every_invalid_field_is_reported() {
+        let form = FormData {
+            name: "<script>".into(),
+            email: "not-an-email".into(),
+        };
+
+        let errors = NewSubscriber::try_from(form).err().unwrap().errors;
+
+        let codes: Vec<_> = errors.iter().map(|e| (e.field, e.code)).collect();
+        assert_eq!(codes, vec![("email", "invalid_format"), ("name", "forbidden_character")]);
+    }
+
+    #[test]
+    fn a_valid_form_is_accepted() {
+        let form = FormData {
+            name: "Ursula Le Guin".into(),
+            email: "ursula@example.com".into(),
+        };
+
+        assert!(NewSubscriber::try_from(form).is_ok());
+    }
+}
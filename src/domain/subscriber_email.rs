@@ -1,18 +1,74 @@
 use validator::ValidateEmail;
+use crate::domain::ValidationPolicy;
 
 #[derive(Debug)]
 pub struct SubscriberEmail(String);
 
+/// 订阅者邮箱的校验失败原因
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum SubscriberEmailError {
+    #[error("The subscriber email cannot be empty.")]
+    Empty,
+    #[error("{0} is not a valid subscriber email.")]
+    InvalidFormat(String),
+    #[error("Subscriptions from {0} are not accepted.")]
+    DomainNotAllowed(String),
+    #[error("Plus-addressed emails are not accepted.")]
+    PlusAddressing,
+}
+
+impl SubscriberEmailError {
+    /// 机器可读的错误码，供前端映射到表单字段
+    pub fn code(&self) -> &'static str {
+        match self {
+            SubscriberEmailError::Empty => "empty",
+            SubscriberEmailError::InvalidFormat(_) => "invalid_format",
+            SubscriberEmailError::DomainNotAllowed(_) => "domain_not_allowed",
+            SubscriberEmailError::PlusAddressing => "plus_addressing",
+        }
+    }
+}
+
 impl SubscriberEmail {
-    pub fn parse(s: String) -> Result<SubscriberEmail, String> {
-        if s.validate_email() {
-            Ok(SubscriberEmail(s))
+    /// 校验并规范化邮箱：去除首尾空白，域名转为小写的 ASCII（国际化域名转为 punycode）
+    /// 本地部分保留原始大小写用于展示，去重由数据库中 `lower(email)` 的唯一索引负责
+    pub fn parse(s: String) -> Result<SubscriberEmail, SubscriberEmailError> {
+        let trimmed = s.trim();
+        if trimmed.is_empty() {
+            return Err(SubscriberEmailError::Empty);
+        }
+        let normalized = normalize(trimmed).ok_or_else(|| SubscriberEmailError::InvalidFormat(s.clone()))?;
+        if normalized.validate_email() {
+            Ok(SubscriberEmail(normalized))
         } else {
-            Err(format!("{} is not a valid subscriber email.", s))
+            Err(SubscriberEmailError::InvalidFormat(s))
+        }
+    }
+
+    /// 在格式校验之外应用部署配置的订阅规则
+    /// 仅用于新订阅：发件人地址与已入库的地址只需通过 `parse`
+    pub fn parse_with(s: String, policy: &ValidationPolicy) -> Result<SubscriberEmail, SubscriberEmailError> {
+        let email = Self::parse(s)?;
+        let (local_part, domain) = email
+            .0
+            .rsplit_once('@')
+            .expect("A parsed email always contains an `@`");
+        if !policy.allow_plus_addressing && local_part.contains('+') {
+            return Err(SubscriberEmailError::PlusAddressing);
         }
+        if !policy.accepts_domain(domain) {
+            return Err(SubscriberEmailError::DomainNotAllowed(domain.to_owned()));
+        }
+        Ok(email)
     }
 }
 
+fn normalize(email: &str) -> Option<String> {
+    let (local_part, domain) = email.rsplit_once('@')?;
+    let domain = idna::domain_to_ascii(domain).ok()?;
+    Some(format!("{}@{}", local_part, domain))
+}
+
 impl AsRef<str> for SubscriberEmail {
     fn as_ref(&self) -> &str {
         &self.0
@@ -21,7 +77,8 @@ impl AsRef<str> for SubscriberEmail {
 
 #[cfg(test)]
 mod tests {
-    use super::SubscriberEmail;
+    use super::{SubscriberEmail, SubscriberEmailError};
+    use crate::domain::ValidationPolicy;
     use claim::{assert_err, assert_ok};
     use fake::{Fake, faker::internet::en::SafeEmail};
     use quickcheck::{Arbitrary, Gen};
@@ -42,10 +99,28 @@ mod tests {
         assert_ok!(SubscriberEmail::parse(valid_email.0));
     }
 
+    #[test]
+    fn surrounding_whitespace_is_trimmed() {
+        let email = SubscriberEmail::parse("  ursula@domain.com\n".to_string()).unwrap();
+        assert_eq!(email.as_ref(), "ursula@domain.com");
+    }
+
+    #[test]
+    fn the_domain_is_lowercased_and_the_local_part_keeps_its_casing() {
+        let email = SubscriberEmail::parse("Ursula.LeGuin@Domain.COM".to_string()).unwrap();
+        assert_eq!(email.as_ref(), "Ursula.LeGuin@domain.com");
+    }
+
+    #[test]
+    fn internationalized_domains_are_converted_to_punycode() {
+        let email = SubscriberEmail::parse("ursula@Bücher.example".to_string()).unwrap();
+        assert_eq!(email.as_ref(), "ursula@xn--bcher-kva.example");
+    }
+
     #[test]
     fn empty_string_is_rejected() {
         let email = "".to_string();
-        assert_err!(SubscriberEmail::parse(email));
+        assert_eq!(SubscriberEmail::parse(email).unwrap_err(), SubscriberEmailError::Empty);
     }
 
     #[test]
@@ -59,4 +134,31 @@ mod tests {
         let email = "@domain.com".to_string();
         assert_err!(SubscriberEmail::parse(email));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn plus_addressing_can_be_disabled() {
+        let policy = ValidationPolicy {
+            allow_plus_addressing: false,
+            ..ValidationPolicy::default()
+        };
+
+        assert_ok!(SubscriberEmail::parse_with("ursula+news@domain.com".into(), &ValidationPolicy::default()));
+        assert_eq!(
+            SubscriberEmail::parse_with("ursula+news@domain.com".into(), &policy).unwrap_err(),
+            SubscriberEmailError::PlusAddressing
+        );
+    }
+
+    #[test]
+    fn blocked_domains_are_matched_after_normalization() {
+        let policy = ValidationPolicy {
+            blocked_email_domains: vec!["xn--bcher-kva.example".into()],
+            ..ValidationPolicy::default()
+        };
+
+        assert_eq!(
+            SubscriberEmail::parse_with("ursula@BÜCHER.example".into(), &policy).unwrap_err(),
+            SubscriberEmailError::DomainNotAllowed("xn--bcher-kva.example".into())
+        );
+    }
+}
@@ -1,20 +1,73 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::domain::ValidationPolicy;
+
 #[derive(Debug)]
 pub struct SubscriberName(String);
 
+/// 订阅者名称的校验失败原因
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum SubscriberNameError {
+    #[error("The subscriber name cannot be empty.")]
+    Empty,
+    #[error("The subscriber name cannot be longer than {0} characters.")]
+    TooLong(usize),
+    #[error("The subscriber name contains forbidden characters.")]
+    ForbiddenCharacters,
+    #[error("The subscriber name contains control, bidirectional or zero-width characters.")]
+    InvisibleCharacters,
+}
+
+impl SubscriberNameError {
+    /// 机器可读的错误码，供前端映射到表单字段
+    pub fn code(&self) -> &'static str {
+        match self {
+            SubscriberNameError::Empty => "empty",
+            SubscriberNameError::TooLong(_) => "too_long",
+            SubscriberNameError::ForbiddenCharacters | SubscriberNameError::InvisibleCharacters => {
+                "forbidden_character"
+            }
+        }
+    }
+}
+
 impl SubscriberName {
-    pub fn parse(s: String) -> Result<Self, String> {
-        let is_empty_or_whitespace = s.trim().is_empty();
-        let is_too_long = s.chars().count() > 256;
-        let forbidden_characters = ['/', '(', ')', '"', '<', '>', '\\', '{', '}'];
-        let contains_forbidden_characters = s.chars().any(|c| forbidden_characters.contains(&c));
-        if is_empty_or_whitespace || is_too_long || contains_forbidden_characters {
-            Err(format!("'{}' is not a valid subscriber name.", s))
+    /// 按默认规则校验
+    pub fn parse(s: String) -> Result<Self, SubscriberNameError> {
+        Self::parse_with(s, &ValidationPolicy::default())
+    }
+
+    /// 先做 NFC 规范化，再按字素簇（用户感知的字符）计算长度
+    pub fn parse_with(s: String, policy: &ValidationPolicy) -> Result<Self, SubscriberNameError> {
+        let s: String = s.nfc().collect();
+        if s.trim().is_empty() {
+            Err(SubscriberNameError::Empty)
+        } else if s.graphemes(true).count() > policy.max_name_length {
+            Err(SubscriberNameError::TooLong(policy.max_name_length))
+        } else if s.chars().any(|c| policy.forbidden_characters.contains(&c)) {
+            Err(SubscriberNameError::ForbiddenCharacters)
+        } else if s.chars().any(is_invisible_character) {
+            Err(SubscriberNameError::InvisibleCharacters)
         } else {
             Ok(Self(s))
         }
     }
 }
 
+/// 控制字符、双向文本控制符与零宽字符：在邮件问候语中可用于伪装显示内容
+fn is_invisible_character(c: char) -> bool {
+    c.is_control()
+        || matches!(
+            c,
+            '\u{061C}'                  // ARABIC LETTER MARK
+            | '\u{200B}'..='\u{200F}'   // 零宽空格/连接符/非连接符、LRM、RLM
+            | '\u{202A}'..='\u{202E}'   // LRE、RLE、PDF、LRO、RLO
+            | '\u{2060}'..='\u{2064}'   // WORD JOINER 及不可见运算符
+            | '\u{2066}'..='\u{2069}'   // LRI、RLI、FSI、PDI
+            | '\u{FEFF}'                // ZERO WIDTH NO-BREAK SPACE
+        )
+}
+
 impl AsRef<str> for SubscriberName {
     fn as_ref(&self) -> &str {
         &self.0
@@ -23,8 +76,78 @@ impl AsRef<str> for SubscriberName {
 
 #[cfg(test)]
 mod tests {
-    use super::SubscriberName;
+    use super::{SubscriberName, SubscriberNameError};
+    use crate::domain::ValidationPolicy;
     use claim::{assert_err, assert_ok};
+    use fake::{Fake, faker::name::en::Name};
+    use quickcheck::{Arbitrary, Gen};
+
+    const INVISIBLE_CHARACTERS: &[char] = &[
+        '\u{0000}', '\u{0007}', '\u{001B}', '\u{007F}', '\u{0085}', '\u{061C}', '\u{200B}', '\u{200C}',
+        '\u{200D}', '\u{200E}', '\u{200F}', '\u{202A}', '\u{202B}', '\u{202C}', '\u{202D}', '\u{202E}',
+        '\u{2060}', '\u{2066}', '\u{2067}', '\u{2068}', '\u{2069}', '\u{FEFF}',
+    ];
+
+    const NON_ASCII_NAMES: &[&str] = &["Zoë Saldaña", "José Martí", "李小龙", "Ngô Bảo Châu", "Øystein Ålen", "Дмитрий"];
+
+    #[derive(Debug, Clone)]
+    struct ValidNameFixture(pub String);
+
+    impl Arbitrary for ValidNameFixture {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            if bool::arbitrary(g) {
+                Self(Name().fake_with_rng(g))
+            } else {
+                Self(NON_ASCII_NAMES[usize::arbitrary(g) % NON_ASCII_NAMES.len()].to_string())
+            }
+        }
+    }
+
+    /// 在合法名称的任意位置插入一个不可见字符
+    #[derive(Debug, Clone)]
+    struct SpoofedNameFixture(pub String);
+
+    impl Arbitrary for SpoofedNameFixture {
+        fn arbitrary<G: Gen>(g: &mut G) -> Self {
+            let mut chars: Vec<char> = ValidNameFixture::arbitrary(g).0.chars().collect();
+            let position = usize::arbitrary(g) % (chars.len() + 1);
+            let invisible = INVISIBLE_CHARACTERS[usize::arbitrary(g) % INVISIBLE_CHARACTERS.len()];
+            chars.insert(position, invisible);
+            Self(chars.into_iter().collect())
+        }
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn valid_names_are_parsed_successfully(valid_name: ValidNameFixture) {
+        assert_ok!(SubscriberName::parse(valid_name.0));
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn names_containing_invisible_characters_are_rejected(spoofed_name: SpoofedNameFixture) {
+        assert_eq!(
+            SubscriberName::parse(spoofed_name.0).unwrap_err(),
+            SubscriberNameError::InvisibleCharacters
+        );
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn length_is_measured_in_grapheme_clusters(length: usize) {
+        let length = length % 300 + 1;
+        // 每个字素簇由基字符和两个组合符号构成，NFC 规范化后仍占多个码点
+        let name = "a\u{0301}\u{0316}".repeat(length);
+        let result = SubscriberName::parse(name);
+        if length <= 256 {
+            assert_ok!(result);
+        } else {
+            assert_eq!(result.unwrap_err(), SubscriberNameError::TooLong(256));
+        }
+    }
+
+    #[test]
+    fn names_are_normalized_to_nfc() {
+        let name = SubscriberName::parse("Zoe\u{0308}".to_string()).unwrap();
+        assert_eq!(name.as_ref(), "Zo\u{00EB}");
+    }
 
     #[test]
     fn a_256_character_name_is_valid() {
@@ -35,13 +158,22 @@ mod tests {
     #[test]
     fn a_name_longer_than_256_characters_is_rejected() {
         let name = "a".repeat(257);
-        assert_err!(SubscriberName::parse(name));
+        assert_eq!(SubscriberName::parse(name).unwrap_err(), SubscriberNameError::TooLong(256));
+    }
+
+    #[test]
+    fn emoji_sequences_count_as_a_single_character_but_zero_width_joiners_are_rejected() {
+        assert_ok!(SubscriberName::parse("🇳🇴".repeat(256)));
+        assert_eq!(
+            SubscriberName::parse("👩\u{200D}💻".to_string()).unwrap_err(),
+            SubscriberNameError::InvisibleCharacters
+        );
     }
 
     #[test]
     fn whitespace_only_names_are_rejected() {
         let name = " ".to_string();
-        assert_err!(SubscriberName::parse(name));  
+        assert_eq!(SubscriberName::parse(name).unwrap_err(), SubscriberNameError::Empty);
     }
 
     #[test]
@@ -54,10 +186,29 @@ mod tests {
     fn names_containing_forbidden_characters_are_rejected() {
         for name in &['/', '(', ')', '"', '<', '>', '\\', '{', '}'] {
             let name = name.to_string();
-            assert_err!(SubscriberName::parse(name));
+            assert_eq!(SubscriberName::parse(name).unwrap_err(), SubscriberNameError::ForbiddenCharacters);
         }
     }
 
+    #[test]
+    fn the_policy_overrides_the_length_limit_and_forbidden_characters() {
+        let policy = ValidationPolicy {
+            max_name_length: 5,
+            forbidden_characters: vec!['@'],
+            ..ValidationPolicy::default()
+        };
+
+        assert_ok!(SubscriberName::parse_with("(cxw)".into(), &policy));
+        assert_eq!(
+            SubscriberName::parse_with("progcxw".into(), &policy).unwrap_err(),
+            SubscriberNameError::TooLong(5)
+        );
+        assert_eq!(
+            SubscriberName::parse_with("c@x".into(), &policy).unwrap_err(),
+            SubscriberNameError::ForbiddenCharacters
+        );
+    }
+
     #[test]
     fn a_valid_name_is_parsed_successfully() {
         let name = "Ursula Le Guin".to_string();
@@ -0,0 +1,88 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// 退订令牌：`{subscriber_id}.{base64url(HMAC-SHA256(subscriber_id))}`
+/// 无需落库，持有签名密钥即可校验，且无法通过修改 id 伪造其他订阅者的令牌
+#[derive(Debug)]
+pub struct UnsubscribeToken(String);
+
+impl UnsubscribeToken {
+    pub fn generate(subscriber_id: Uuid, hmac_secret: &[u8]) -> Self {
+        let signature = sign(subscriber_id, hmac_secret).finalize().into_bytes();
+        Self(format!("{}.{}", subscriber_id, URL_SAFE_NO_PAD.encode(signature)))
+    }
+
+    /// 校验签名，成功时返回令牌对应的订阅者 id
+    pub fn verify(token: &str, hmac_secret: &[u8]) -> Result<Uuid, String> {
+        let (subscriber_id, signature) = token
+            .split_once('.')
+            .ok_or("The unsubscribe token is malformed.")?;
+        let subscriber_id = Uuid::parse_str(subscriber_id)
+            .map_err(|_| "The unsubscribe token is malformed.")?;
+        let signature = URL_SAFE_NO_PAD
+            .decode(signature)
+            .map_err(|_| "The unsubscribe token is malformed.")?;
+        sign(subscriber_id, hmac_secret)
+            .verify_slice(&signature)
+            .map_err(|_| "The unsubscribe token signature is invalid.")?;
+        Ok(subscriber_id)
+    }
+}
+
+fn sign(subscriber_id: Uuid, hmac_secret: &[u8]) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(hmac_secret).expect("HMAC accepts keys of any size");
+    mac.update(b"unsubscribe:");
+    mac.update(subscriber_id.as_bytes());
+    mac
+}
+
+impl AsRef<str> for UnsubscribeToken {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UnsubscribeToken;
+    use claim::assert_err;
+    use uuid::Uuid;
+
+    const SECRET: &[u8] = b"a-secret-used-only-in-tests";
+
+    #[test]
+    fn a_generated_token_verifies_to_the_same_subscriber() {
+        let subscriber_id = Uuid::new_v4();
+        let token = UnsubscribeToken::generate(subscriber_id, SECRET);
+
+        assert_eq!(UnsubscribeToken::verify(token.as_ref(), SECRET), Ok(subscriber_id));
+    }
+
+    #[test]
+    fn tokens_signed_with_another_secret_are_rejected() {
+        let token = UnsubscribeToken::generate(Uuid::new_v4(), b"another-secret");
+
+        assert_err!(UnsubscribeToken::verify(token.as_ref(), SECRET));
+    }
+
+    #[test]
+    fn tokens_for_a_different_subscriber_are_rejected() {
+        let token = UnsubscribeToken::generate(Uuid::new_v4(), SECRET);
+        let (_, signature) = token.as_ref().split_once('.').unwrap();
+        let forged = format!("{}.{}", Uuid::new_v4(), signature);
+
+        assert_err!(UnsubscribeToken::verify(&forged, SECRET));
+    }
+
+    #[test]
+    fn malformed_tokens_are_rejected() {
+        assert_err!(UnsubscribeToken::verify("", SECRET));
+        assert_err!(UnsubscribeToken::verify("not-a-uuid.c2ln", SECRET));
+        assert_err!(UnsubscribeToken::verify(&Uuid::new_v4().to_string(), SECRET));
+    }
+}
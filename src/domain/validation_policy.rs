@@ -0,0 +1,77 @@
+/// 订阅字段的校验规则，由配置中的 `validation` 段生成
+/// 默认值与配置化之前硬编码的规则一致
+#[derive(Debug, Clone)]
+pub struct ValidationPolicy {
+    pub max_name_length: usize,             // 名称的最大长度（按字素簇计）
+    pub forbidden_characters: Vec<char>,    // 名称中禁止出现的字符
+    pub allowed_email_domains: Vec<String>, // 允许的邮箱域名，为空表示不限制
+    pub blocked_email_domains: Vec<String>, // 禁止的邮箱域名，优先于允许列表
+    pub allow_plus_addressing: bool,        // 是否接受 `user+tag@domain` 形式的地址
+}
+
+impl Default for ValidationPolicy {
+    fn default() -> Self {
+        Self {
+            max_name_length: 256,
+            forbidden_characters: vec!['/', '(', ')', '"', '<', '>', '\\', '{', '}'],
+            allowed_email_domains: Vec::new(),
+            blocked_email_domains: Vec::new(),
+            allow_plus_addressing: true,
+        }
+    }
+}
+
+impl ValidationPolicy {
+    /// 域名是否可以订阅；列表中的域名同时匹配其所有子域名
+    /// `domain` 应为规范化后的小写 ASCII 域名
+    pub fn accepts_domain(&self, domain: &str) -> bool {
+        let matches = |listed: &String| domain_matches(domain, listed);
+        if self.blocked_email_domains.iter().any(matches) {
+            return false;
+        }
+        self.allowed_email_domains.is_empty() || self.allowed_email_domains.iter().any(matches)
+    }
+}
+
+fn domain_matches(domain: &str, listed: &str) -> bool {
+    domain == listed
+        || domain
+            .strip_suffix(listed)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ValidationPolicy;
+
+    #[test]
+    fn the_default_policy_accepts_every_domain() {
+        assert!(ValidationPolicy::default().accepts_domain("example.com"));
+    }
+
+    #[test]
+    fn blocked_domains_and_their_subdomains_are_rejected() {
+        let policy = ValidationPolicy {
+            blocked_email_domains: vec!["spam.example".into()],
+            ..ValidationPolicy::default()
+        };
+
+        assert!(!policy.accepts_domain("spam.example"));
+        assert!(!policy.accepts_domain("mail.spam.example"));
+        assert!(policy.accepts_domain("notspam.example"));
+    }
+
+    #[test]
+    fn only_allowed_domains_are_accepted_when_the_allow_list_is_set() {
+        let policy = ValidationPolicy {
+            allowed_email_domains: vec!["corp.example".into()],
+            blocked_email_domains: vec!["old.corp.example".into()],
+            ..ValidationPolicy::default()
+        };
+
+        assert!(policy.accepts_domain("corp.example"));
+        assert!(policy.accepts_domain("eu.corp.example"));
+        assert!(!policy.accepts_domain("old.corp.example"));
+        assert!(!policy.accepts_domain("gmail.com"));
+    }
+}
@@ -0,0 +1,53 @@
+/// 客户端通过 `Idempotency-Key` 请求头提供的幂等键
+#[derive(Debug)]
+pub struct IdempotencyKey(String);
+
+impl IdempotencyKey {
+    const MAX_LENGTH: usize = 50;
+}
+
+impl TryFrom<String> for IdempotencyKey {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        if s.trim().is_empty() {
+            return Err("The idempotency key cannot be empty".into());
+        }
+        if s.chars().count() > Self::MAX_LENGTH {
+            return Err(format!(
+                "The idempotency key cannot be longer than {} characters",
+                Self::MAX_LENGTH
+            ));
+        }
+        Ok(Self(s))
+    }
+}
+
+impl AsRef<str> for IdempotencyKey {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IdempotencyKey;
+
+    #[test]
+    fn empty_keys_are_rejected() {
+        assert!(IdempotencyKey::try_from("".to_string()).is_err());
+        assert!(IdempotencyKey::try_from("   ".to_string()).is_err());
+    }
+
+    #[test]
+    fn keys_longer_than_the_limit_are_rejected() {
+        assert!(IdempotencyKey::try_from("a".repeat(50)).is_ok());
+        assert!(IdempotencyKey::try_from("a".repeat(51)).is_err());
+    }
+
+    #[test]
+    fn the_limit_counts_characters_rather_than_bytes() {
+        assert!(IdempotencyKey::try_from("键".repeat(50)).is_ok());
+        assert!(IdempotencyKey::try_from("键".repeat(51)).is_err());
+    }
+}
@@ -0,0 +1,5 @@
+mod key;
+mod persistence;
+
+pub use key::IdempotencyKey;
+pub use persistence::{delete_expired_idempotency_keys, save_response, try_processing, NextAction};
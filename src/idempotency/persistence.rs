@@ -0,0 +1,151 @@
+use super::IdempotencyKey;
+use actix_web::body::to_bytes;
+use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
+use sqlx::{PgPool, Postgres, Transaction};
+use std::time::Duration;
+use uuid::Uuid;
+
+#[derive(Debug, sqlx::Type)]
+#[sqlx(type_name = "header_pair")]
+struct HeaderPairRecord {
+    name: String,
+    value: Vec<u8>,
+}
+
+/// 幂等检查的结果
+pub enum NextAction {
+    /// 首次请求：在返回的事务中执行业务逻辑，并通过 [`save_response`] 保存响应后提交
+    StartProcessing(Transaction<'static, Postgres>),
+    /// 重复请求：直接返回首次请求保存的响应
+    ReturnSavedResponse(HttpResponse),
+}
+
+/// 占位插入幂等键；插入成功说明是首次请求
+/// 并发的重复请求会阻塞在插入上，直到首个请求的事务提交后读到其保存的响应
+#[tracing::instrument(skip(pool, idempotency_key), fields(idempotency_key = %idempotency_key.as_ref()))]
+pub async fn try_processing(
+    pool: &PgPool,
+    idempotency_key: &IdempotencyKey,
+    user_id: Uuid,
+) -> Result<NextAction, anyhow::Error> {
+    let mut transaction = pool.begin().await?;
+    let n_inserted_rows = sqlx::query!(
+        r#"
+        INSERT INTO idempotency (
+            user_id,
+            idempotency_key,
+            created_at
+        )
+        VALUES ($1, $2, now())
+        ON CONFLICT DO NOTHING
+        "#,
+        user_id,
+        idempotency_key.as_ref(),
+    )
+        .execute(&mut *transaction)
+        .await?
+        .rows_affected();
+    if n_inserted_rows > 0 {
+        Ok(NextAction::StartProcessing(transaction))
+    } else {
+        let saved_response = get_saved_response(pool, idempotency_key, user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("We expected a saved response, we didn't find it"))?;
+        Ok(NextAction::ReturnSavedResponse(saved_response))
+    }
+}
+
+async fn get_saved_response(
+    pool: &PgPool,
+    idempotency_key: &IdempotencyKey,
+    user_id: Uuid,
+) -> Result<Option<HttpResponse>, anyhow::Error> {
+    let saved_response = sqlx::query!(
+        r#"
+        SELECT
+            response_status_code as "response_status_code!",
+            response_headers as "response_headers!: Vec<HeaderPairRecord>",
+            response_body as "response_body!"
+        FROM idempotency
+        WHERE
+            user_id = $1 AND
+            idempotency_key = $2
+        "#,
+        user_id,
+        idempotency_key.as_ref(),
+    )
+        .fetch_optional(pool)
+        .await?;
+    let Some(r) = saved_response else {
+        return Ok(None);
+    };
+
+    let status_code = StatusCode::from_u16(r.response_status_code.try_into()?)?;
+    let mut response = HttpResponse::build(status_code);
+    for HeaderPairRecord { name, value } in r.response_headers {
+        response.append_header((name, value));
+    }
+    Ok(Some(response.body(r.response_body)))
+}
+
+/// 保存响应并提交事务，返回可直接交给 actix 的响应
+#[tracing::instrument(skip_all, fields(idempotency_key = %idempotency_key.as_ref()))]
+pub async fn save_response(
+    mut transaction: Transaction<'static, Postgres>,
+    idempotency_key: &IdempotencyKey,
+    user_id: Uuid,
+    http_response: HttpResponse,
+) -> Result<HttpResponse, anyhow::Error> {
+    let (response_head, body) = http_response.into_parts();
+    let body = to_bytes(body).await.map_err(|e| anyhow::anyhow!("{}", e))?;
+    let status_code = response_head.status().as_u16() as i16;
+    let headers = response_head
+        .headers()
+        .iter()
+        .map(|(name, value)| HeaderPairRecord {
+            name: name.as_str().to_owned(),
+            value: value.as_bytes().to_owned(),
+        })
+        .collect::<Vec<_>>();
+
+    // sqlx 的编译期检查不认识自定义复合类型，这里改用 unchecked 版本
+    sqlx::query_unchecked!(
+        r#"
+        UPDATE idempotency
+        SET
+            response_status_code = $3,
+            response_headers = $4,
+            response_body = $5
+        WHERE
+            user_id = $1 AND
+            idempotency_key = $2
+        "#,
+        user_id,
+        idempotency_key.as_ref(),
+        status_code,
+        headers,
+        body.as_ref(),
+    )
+        .execute(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+
+    Ok(response_head.set_body(body).map_into_boxed_body())
+}
+
+/// 删除创建时间早于 `ttl` 的幂等键，返回删除的行数
+#[tracing::instrument(skip(pool))]
+pub async fn delete_expired_idempotency_keys(pool: &PgPool, ttl: Duration) -> Result<u64, sqlx::Error> {
+    let ttl_seconds = ttl.as_secs_f64();
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM idempotency
+        WHERE created_at < now() - make_interval(secs => $1)
+        "#,
+        ttl_seconds,
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
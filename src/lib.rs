@@ -1,5 +1,8 @@
+pub mod authentication;
 pub mod routes;
 pub mod domain;
+pub mod idempotency;
+pub mod suppression;
 pub mod service;
 pub mod startup;
 pub mod configuration;
@@ -1,30 +1,82 @@
-use actix_try::{configuration::get_configuration, startup::run, telemetry::setup_logging};
-use sqlx::postgres::PgPool;
-use std::net::TcpListener;
-use actix_try::service::email_client::EmailClient;
+use actix_try::{configuration::{merge_configuration, ConfigurationError, ConfigurationSource}, startup::Application, telemetry::setup_logging};
+use actix_try::service::issue_delivery_worker::{run_worker_until_stopped, UnsubscribeLinks};
+use actix_try::service::idempotency_expiry_worker::run_idempotency_expiry_until_stopped;
+use clap::Parser;
+use std::path::PathBuf;
 
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    setup_logging("actix_try".into(), "info".into(), std::io::stdout);
+/// 邮件订阅服务
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// 配置文件目录，默认为当前目录下的 `configuration`
+    #[arg(long, value_name = "DIR")]
+    config_dir: Option<PathBuf>,
+    /// 运行环境（local、staging、test、production 或自定义名称），优先于 APP_ENVIRONMENT
+    #[arg(long, value_name = "NAME")]
+    environment: Option<String>,
+    /// 覆盖单个配置项，优先于配置文件与环境变量，可重复使用，如 `--set email.smtp_port=587`
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_override)]
+    overrides: Vec<(String, String)>,
+    /// 打印合并后的配置（secret 已隐去）并退出
+    #[arg(long)]
+    print_config: bool,
+}
+
+fn parse_override(s: &str) -> Result<(String, String), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("`{}` is not in the form KEY=VALUE", s))?;
+    Ok((key.trim().to_owned(), value.to_owned()))
+}
 
-    let configuration = get_configuration().expect("Failed to read configuration");
-    let connection_string = configuration.database.connection_string();
-    let connection_pool = PgPool::connect_lazy(&connection_string)
-        .expect("Failed to connect to Postgres");
+#[actix_web::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    // `--print-config` 的输出占用 stdout，日志改写到 stderr，保证输出可以直接交给 jq 等工具
+    if cli.print_config {
+        setup_logging("actix_try".into(), "info".into(), std::io::stderr);
+    } else {
+        setup_logging("actix_try".into(), "info".into(), std::io::stdout);
+    }
 
-    let email_settings = configuration.email.clone();
-    let sender = email_settings.sender()
-        .expect("invalid sender email address");
-    let email_client = EmailClient::new(
-        email_settings.smtp_host,
-        email_settings.smtp_port,
-        email_settings.smtp_username,
-        email_settings.smtp_password,
-        email_settings.use_starttls,
-        sender,
+    let mut source = ConfigurationSource::current_dir()?;
+    if let Some(config_dir) = cli.config_dir {
+        source.directory = config_dir;
+    }
+    source.environment = cli.environment;
+    source.overrides = cli.overrides;
+    let configuration = merge_configuration(&source)?;
+    // 先输出合并结果再校验，配置有误时也能据此排查
+    if cli.print_config {
+        println!("{}", serde_json::to_string_pretty(&configuration)?);
+    }
+    // 配置有误时列出所有问题后退出
+    configuration.validate().map_err(ConfigurationError::Invalid)?;
+    if cli.print_config {
+        return Ok(());
+    }
+    let application = Application::build(configuration.clone()).await?;
+    let worker = run_worker_until_stopped(
+        application.connection_pool(),
+        application.email_client(),
+        configuration.delivery.retry_policy(),
+        UnsubscribeLinks::new(
+            configuration.application.base_url,
+            configuration.application.hmac_secret,
+        ),
+        application.suppression_list(),
+    );
+    let idempotency_expiry = run_idempotency_expiry_until_stopped(
+        application.connection_pool(),
+        configuration.idempotency.ttl(),
+        configuration.idempotency.sweep_interval(),
     );
 
-    let address = format!("{}:{}", configuration.application.host, configuration.application.port);
-    let listener = TcpListener::bind(address)?;
-    run(listener, connection_pool, email_client)?.await
+    // 任意一方退出即结束进程
+    tokio::select! {
+        outcome = application.run_until_stopped() => outcome?,
+        _ = worker => tracing::error!("Background worker exited unexpectedly"),
+        _ = idempotency_expiry => tracing::error!("Idempotency expiry worker exited unexpectedly"),
+    }
+    Ok(())
 }
@@ -0,0 +1,96 @@
+use crate::routes::{error_chain_fmt, ProblemDetails};
+use crate::suppression::{add_blocklist_entry, list_blocklist_entries, remove_blocklist_entry, BlocklistRule};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpResponse, ResponseError};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct BlocklistRequest {
+    /// 完整地址、域名或含 `*` 的通配模式
+    pub entry: String,
+}
+
+/// 黑名单管理接口的错误
+#[derive(thiserror::Error)]
+pub enum BlocklistError {
+    #[error("{0}")]
+    Validation(String),
+    #[error("{context}")]
+    Storage {
+        context: &'static str,
+        #[source]
+        source: sqlx::Error,
+    },
+}
+
+impl BlocklistError {
+    fn storage(context: &'static str) -> impl FnOnce(sqlx::Error) -> Self {
+        move |source| Self::Storage { context, source }
+    }
+}
+
+impl std::fmt::Debug for BlocklistError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for BlocklistError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            BlocklistError::Validation(_) => StatusCode::BAD_REQUEST,
+            BlocklistError::Storage { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            BlocklistError::Validation(e) => ProblemDetails::validation(e.clone(), Vec::new()).into_response(),
+            _ => HttpResponse::new(self.status_code()),
+        }
+    }
+}
+
+#[tracing::instrument(name = "List the email blocklist", skip(pool))]
+pub async fn list_blocklist(pool: web::Data<PgPool>) -> Result<HttpResponse, BlocklistError> {
+    let entries = list_blocklist_entries(&pool)
+        .await
+        .map_err(BlocklistError::storage("Failed to list the blocklist entries."))?;
+    Ok(HttpResponse::Ok().json(entries))
+}
+
+/// 新增规则；规则无效时返回 400，已存在时返回 409
+#[tracing::instrument(
+    name = "Add an entry to the email blocklist",
+    skip(body, pool),
+    fields(entry = %body.entry)
+)]
+pub async fn add_to_blocklist(
+    body: web::Json<BlocklistRequest>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlocklistError> {
+    let rule = BlocklistRule::parse(&body.entry).map_err(BlocklistError::Validation)?;
+    let entry = add_blocklist_entry(&pool, &rule)
+        .await
+        .map_err(BlocklistError::storage("Failed to insert the blocklist entry."))?;
+    match entry {
+        Some(entry) => Ok(HttpResponse::Created().json(entry)),
+        None => Ok(HttpResponse::Conflict().finish()),
+    }
+}
+
+#[tracing::instrument(name = "Remove an entry from the email blocklist", skip(pool))]
+pub async fn remove_from_blocklist(
+    id: web::Path<Uuid>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, BlocklistError> {
+    let removed = remove_blocklist_entry(&pool, id.into_inner())
+        .await
+        .map_err(BlocklistError::storage("Failed to delete the blocklist entry."))?;
+    if removed {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(HttpResponse::NotFound().finish())
+    }
+}
@@ -0,0 +1,63 @@
+use crate::authentication::{TypedSession, UserId};
+use crate::routes::login::see_other;
+use actix_web::http::header::ContentType;
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_flash_messages::FlashMessage;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// 管理后台首页，展示当前登录的管理员
+#[tracing::instrument(name = "Admin dashboard", skip(pool), fields(user_id = %*user_id))]
+pub async fn admin_dashboard(
+    user_id: web::ReqData<UserId>,
+    pool: web::Data<PgPool>,
+) -> impl Responder {
+    let username = match get_username(**user_id, &pool).await {
+        Ok(username) => username,
+        Err(_) => return HttpResponse::InternalServerError().finish(),
+    };
+    HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .body(format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta http-equiv="content-type" content="text/html; charset=utf-8">
+    <title>Admin dashboard</title>
+</head>
+<body>
+    <p>Welcome {}!</p>
+    <form name="logoutForm" action="/admin/logout" method="post">
+        <input type="submit" value="Logout">
+    </form>
+</body>
+</html>"#,
+            htmlescape::encode_minimal(&username),
+        ))
+}
+
+/// 退出登录：删除服务端会话并回到登录页
+pub async fn log_out(session: TypedSession) -> impl Responder {
+    session.log_out();
+    FlashMessage::info("You have successfully logged out.").send();
+    see_other("/login")
+}
+
+#[tracing::instrument(skip(pool))]
+async fn get_username(user_id: Uuid, pool: &PgPool) -> Result<String, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT username
+        FROM users
+        WHERE user_id = $1
+        "#,
+        user_id,
+    )
+        .fetch_one(pool)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to execute query: {:?}", e);
+            e
+        })?;
+    Ok(row.username)
+}
@@ -0,0 +1,124 @@
+use crate::routes::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpResponse, ResponseError};
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+/// 死信表中的一条投递记录
+#[derive(serde::Serialize)]
+pub struct DeadLetter {
+    pub newsletter_issue_id: Uuid,
+    pub subscriber_email: String,
+    pub n_retries: i32,
+    pub last_error: String,
+    pub failed_at: DateTime<Utc>,
+}
+
+#[derive(serde::Deserialize)]
+pub struct RequeueRequest {
+    pub newsletter_issue_id: Uuid,
+    pub subscriber_email: String,
+}
+
+/// 死信管理接口的错误
+#[derive(thiserror::Error)]
+pub enum DeadLetterError {
+    #[error("{context}")]
+    Storage {
+        context: &'static str,
+        #[source]
+        source: sqlx::Error,
+    },
+}
+
+impl DeadLetterError {
+    fn storage(context: &'static str) -> impl FnOnce(sqlx::Error) -> Self {
+        move |source| Self::Storage { context, source }
+    }
+}
+
+impl std::fmt::Debug for DeadLetterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for DeadLetterError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            DeadLetterError::Storage { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[tracing::instrument(name = "List dead letter deliveries", skip(pool))]
+pub async fn list_dead_letters(pool: web::Data<PgPool>) -> Result<HttpResponse, DeadLetterError> {
+    let dead_letters = sqlx::query_as!(
+        DeadLetter,
+        r#"
+        SELECT newsletter_issue_id, subscriber_email, n_retries, last_error, failed_at
+        FROM issue_delivery_dead_letters
+        ORDER BY failed_at DESC
+        "#,
+    )
+        .fetch_all(pool.get_ref())
+        .await
+        .map_err(DeadLetterError::storage("Failed to list the dead letter deliveries."))?;
+    Ok(HttpResponse::Ok().json(dead_letters))
+}
+
+/// 将一条死信移回投递队列，重试计数清零并立即可执行
+#[tracing::instrument(
+    name = "Requeue a dead letter delivery",
+    skip(body, pool),
+    fields(
+        newsletter_issue_id = %body.newsletter_issue_id,
+        subscriber_email = %body.subscriber_email,
+    )
+)]
+pub async fn requeue_dead_letter(
+    body: web::Json<RequeueRequest>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, DeadLetterError> {
+    let requeued = requeue(&pool, &body)
+        .await
+        .map_err(DeadLetterError::storage("Failed to move the dead letter back to the delivery queue."))?;
+    if requeued {
+        Ok(HttpResponse::Accepted().finish())
+    } else {
+        Ok(HttpResponse::NotFound().finish())
+    }
+}
+
+async fn requeue(pool: &PgPool, request: &RequeueRequest) -> Result<bool, sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+    let deleted = sqlx::query!(
+        r#"
+        DELETE FROM issue_delivery_dead_letters
+        WHERE
+            newsletter_issue_id = $1 AND
+            subscriber_email = $2
+        "#,
+        request.newsletter_issue_id,
+        request.subscriber_email,
+    )
+        .execute(&mut *transaction)
+        .await?;
+    if deleted.rows_affected() == 0 {
+        return Ok(false);
+    }
+    sqlx::query!(
+        r#"
+        INSERT INTO issue_delivery_queue (newsletter_issue_id, subscriber_email)
+        VALUES ($1, $2)
+        ON CONFLICT DO NOTHING
+        "#,
+        request.newsletter_issue_id,
+        request.subscriber_email,
+    )
+        .execute(&mut *transaction)
+        .await?;
+    transaction.commit().await?;
+    Ok(true)
+}
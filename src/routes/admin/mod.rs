@@ -0,0 +1,7 @@
+pub mod blocklist;
+pub mod dashboard;
+pub mod dead_letters;
+
+pub use blocklist::*;
+pub use dashboard::*;
+pub use dead_letters::*;
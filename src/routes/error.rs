@@ -0,0 +1,75 @@
+use crate::domain::FieldError;
+use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
+
+/// RFC 7807 `application/problem+json` 响应体
+#[derive(Debug, serde::Serialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: &'static str,
+    pub title: &'static str,
+    pub status: u16,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldError>,
+}
+
+impl ProblemDetails {
+    /// 请求字段未通过校验
+    pub fn validation(detail: String, errors: Vec<FieldError>) -> Self {
+        Self {
+            problem_type: "about:blank",
+            title: "Your request parameters didn't validate.",
+            status: StatusCode::BAD_REQUEST.as_u16(),
+            detail,
+            errors,
+        }
+    }
+
+    pub fn into_response(self) -> HttpResponse {
+        let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        HttpResponse::build(status)
+            .content_type("application/problem+json")
+            .json(self)
+    }
+}
+
+/// 按 `source()` 链逐层输出错误，供各路由错误类型的 `Debug` 实现复用
+/// `TracingLogger` 在请求结束时记录 `ResponseError` 的 `Debug` 输出，完整错误链因此只在这一处落日志
+pub fn error_chain_fmt(e: &impl std::error::Error, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    writeln!(f, "{}\n", e)?;
+    let mut current = e.source();
+    while let Some(cause) = current {
+        writeln!(f, "Caused by:\n\t{}", cause)?;
+        current = cause.source();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::error_chain_fmt;
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("outer failure")]
+    struct Outer(#[source] Inner);
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("inner failure")]
+    struct Inner;
+
+    struct Chain(Outer);
+
+    impl std::fmt::Debug for Chain {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            error_chain_fmt(&self.0, f)
+        }
+    }
+
+    #[test]
+    fn every_cause_in_the_chain_is_reported() {
+        let report = format!("{:?}", Chain(Outer(Inner)));
+
+        assert_eq!(report, "outer failure\n\nCaused by:\n\tinner failure\n");
+    }
+}
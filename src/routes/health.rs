@@ -1,6 +1,252 @@
-use actix_web::{HttpResponse, Responder};
+use crate::configuration::HealthSettings;
+use crate::service::email_client::EmailSender;
+use actix_web::{web, HttpResponse, Responder};
+use sqlx::migrate::{Migrate, Migrator};
+use sqlx::{Connection, PgPool};
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// 编译进二进制的迁移，用于与数据库中已执行的版本比对
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
 
 pub async fn health_check() -> impl Responder {
     HttpResponse::Ok().finish()
 }
 
+/// 单项检查或整体的结果
+#[derive(serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass, // 正常
+    Warn, // 可以继续服务，但需要关注
+    Fail, // 检查未通过
+    Skip, // 没有可检查的对象，不影响整体结果
+}
+
+/// 单项检查的结果
+#[derive(serde::Serialize, Debug)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub critical: bool, // 关键检查失败时就绪检查返回 503
+    pub status: CheckStatus,
+    pub latency_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>, // 附加信息或失败原因
+}
+
+/// 健康检查的响应体
+#[derive(serde::Serialize, Debug)]
+pub struct HealthReport {
+    pub status: CheckStatus,
+    pub checks: Vec<CheckResult>,
+}
+
+impl HealthReport {
+    /// 任一关键检查失败为 fail；非关键检查失败或告警为 warn
+    pub fn new(checks: Vec<CheckResult>) -> Self {
+        let status = if checks.iter().any(|c| c.critical && c.status == CheckStatus::Fail) {
+            CheckStatus::Fail
+        } else if checks.iter().any(|c| matches!(c.status, CheckStatus::Warn | CheckStatus::Fail)) {
+            CheckStatus::Warn
+        } else {
+            CheckStatus::Pass
+        };
+        Self { status, checks }
+    }
+}
+
+/// 存活检查：进程能处理请求即返回 200，不检查任何依赖
+pub async fn liveness() -> impl Responder {
+    HttpResponse::Ok().json(HealthReport::new(Vec::new()))
+}
+
+/// 就绪检查：数据库连通性、连接池占用、迁移版本，以及可选的邮件后端连通性
+/// 关键检查失败时返回 503，负载均衡据此暂停转发流量
+#[tracing::instrument(name = "Readiness check", skip_all)]
+pub async fn readiness(
+    pool: web::Data<PgPool>,
+    email_client: web::Data<dyn EmailSender>,
+    settings: web::Data<HealthSettings>,
+) -> impl Responder {
+    let timeout = settings.check_timeout();
+    // 先统计连接池占用，避免把下面检查自身借出的连接算进去
+    let saturation = check_pool_saturation(&pool, settings.pool_saturation_threshold);
+    let (database, migrations, email) = tokio::join!(
+        run_check("database", true, timeout, ping_database(&pool)),
+        run_check("migrations", true, timeout, check_migrations(&pool)),
+        async {
+            if settings.check_email_backend {
+                Some(check_email_backend(email_client.get_ref(), timeout).await)
+            } else {
+                None
+            }
+        },
+    );
+
+    let mut checks = vec![database, migrations, saturation];
+    checks.extend(email);
+    let report = HealthReport::new(checks);
+    if report.status == CheckStatus::Fail {
+        HttpResponse::ServiceUnavailable().json(report)
+    } else {
+        HttpResponse::Ok().json(report)
+    }
+}
+
+/// 执行单项检查并计时；超时视为失败
+async fn run_check(
+    name: &'static str,
+    critical: bool,
+    timeout: Duration,
+    check: impl Future<Output = Result<Option<String>, String>>,
+) -> CheckResult {
+    let start = Instant::now();
+    let outcome = tokio::time::timeout(timeout, check)
+        .await
+        .unwrap_or_else(|_| Err(timed_out(timeout)));
+    check_result(name, critical, start, outcome)
+}
+
+fn timed_out(timeout: Duration) -> String {
+    format!("Timed out after {}ms", timeout.as_millis())
+}
+
+/// 由检查结果与开始时间生成 `CheckResult`，失败时记录日志
+fn check_result(
+    name: &'static str,
+    critical: bool,
+    start: Instant,
+    outcome: Result<Option<String>, String>,
+) -> CheckResult {
+    let latency_ms = start.elapsed().as_secs_f64() * 1000.0;
+    match outcome {
+        Ok(detail) => CheckResult { name, critical, status: CheckStatus::Pass, latency_ms, detail },
+        Err(e) => {
+            tracing::warn!(check = name, error = %e, "Readiness check failed");
+            CheckResult { name, critical, status: CheckStatus::Fail, latency_ms, detail: Some(e) }
+        }
+    }
+}
+
+/// 邮件后端失败只降级为 warn：数据库正常时订阅与发布仍可入队，投递由重试兜底
+async fn check_email_backend(email_client: &dyn EmailSender, timeout: Duration) -> CheckResult {
+    let start = Instant::now();
+    let outcome = match tokio::time::timeout(timeout, email_client.check_connection()).await {
+        Ok(Some(outcome)) => outcome.map(|()| None),
+        // 文件后端没有远端可检查，不计入整体结果
+        Ok(None) => {
+            return CheckResult {
+                name: "email",
+                critical: false,
+                status: CheckStatus::Skip,
+                latency_ms: 0.0,
+                detail: Some("The email backend has no remote endpoint to check".into()),
+            };
+        }
+        Err(_) => Err(timed_out(timeout)),
+    };
+    check_result("email", false, start, outcome)
+}
+
+async fn ping_database(pool: &PgPool) -> Result<Option<String>, String> {
+    let mut connection = pool.acquire().await.map_err(|e| e.to_string())?;
+    connection.ping().await.map_err(|e| e.to_string())?;
+    Ok(None)
+}
+
+/// 编译进二进制的迁移都应已在数据库中执行；数据库版本更新（滚动发布期间）不算失败
+async fn check_migrations(pool: &PgPool) -> Result<Option<String>, String> {
+    let mut connection = pool.acquire().await.map_err(|e| e.to_string())?;
+    let applied: HashSet<i64> = connection
+        .list_applied_migrations()
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+    let expected: Vec<i64> = MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| migration.version)
+        .collect();
+    let pending: Vec<String> = expected
+        .iter()
+        .filter(|version| !applied.contains(version))
+        .map(i64::to_string)
+        .collect();
+    if !pending.is_empty() {
+        return Err(format!("Pending migrations: {}", pending.join(", ")));
+    }
+    Ok(expected.iter().max().map(|version| format!("Schema version {}", version)))
+}
+
+/// 连接池占用比例达到阈值时告警；不是关键检查，占满时请求仍会排队等待连接
+fn check_pool_saturation(pool: &PgPool, threshold: f64) -> CheckResult {
+    let max_connections = pool.options().get_max_connections();
+    let in_use = pool.size().saturating_sub(pool.num_idle() as u32);
+    let saturation = f64::from(in_use) / f64::from(max_connections.max(1));
+    let status = if saturation >= threshold {
+        CheckStatus::Warn
+    } else {
+        CheckStatus::Pass
+    };
+    CheckResult {
+        name: "pool",
+        critical: false,
+        status,
+        latency_ms: 0.0,
+        detail: Some(format!("{} of {} connections in use", in_use, max_connections)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_email_backend, CheckResult, CheckStatus, HealthReport};
+    use crate::domain::SubscriberEmail;
+    use crate::service::email_client::FileEmailClient;
+    use std::time::Duration;
+
+    fn check(critical: bool, status: CheckStatus) -> CheckResult {
+        CheckResult { name: "check", critical, status, latency_ms: 0.0, detail: None }
+    }
+
+    #[test]
+    fn a_failing_critical_check_fails_the_report() {
+        let report = HealthReport::new(vec![check(true, CheckStatus::Fail), check(false, CheckStatus::Pass)]);
+
+        assert_eq!(report.status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn non_critical_failures_and_warnings_only_degrade_the_report() {
+        assert_eq!(
+            HealthReport::new(vec![check(true, CheckStatus::Pass), check(false, CheckStatus::Fail)]).status,
+            CheckStatus::Warn
+        );
+        assert_eq!(
+            HealthReport::new(vec![check(true, CheckStatus::Pass), check(false, CheckStatus::Warn)]).status,
+            CheckStatus::Warn
+        );
+        assert_eq!(HealthReport::new(vec![check(true, CheckStatus::Pass)]).status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn skipped_checks_do_not_affect_the_report() {
+        assert_eq!(
+            HealthReport::new(vec![check(true, CheckStatus::Pass), check(false, CheckStatus::Skip)]).status,
+            CheckStatus::Pass
+        );
+    }
+
+    #[tokio::test]
+    async fn backends_without_a_remote_are_skipped_without_a_latency() {
+        let sender = SubscriberEmail::parse("sender@example.com".into()).unwrap();
+        let email_client = FileEmailClient::new(std::env::temp_dir(), sender);
+
+        let result = check_email_backend(&email_client, Duration::from_secs(1)).await;
+
+        assert_eq!(result.status, CheckStatus::Skip);
+        assert_eq!(result.latency_ms, 0.0);
+    }
+}
@@ -0,0 +1,85 @@
+use crate::authentication::{validate_credentials, AuthError, Credentials, TypedSession};
+use actix_web::http::header::{ContentType, LOCATION};
+use actix_web::{web, HttpResponse, Responder};
+use actix_web_flash_messages::{FlashMessage, IncomingFlashMessages};
+use sqlx::PgPool;
+use std::fmt::Write;
+
+#[derive(serde::Deserialize)]
+pub struct LoginFormData {
+    username: String,
+    password: String,
+}
+
+/// 登录页，展示上一次请求留下的 flash 消息
+pub async fn login_form(flash_messages: IncomingFlashMessages) -> impl Responder {
+    let mut messages_html = String::new();
+    for message in flash_messages.iter() {
+        writeln!(messages_html, "<p><i>{}</i></p>", htmlescape::encode_minimal(message.content())).unwrap();
+    }
+    HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .body(format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta http-equiv="content-type" content="text/html; charset=utf-8">
+    <title>Login</title>
+</head>
+<body>
+    {messages_html}
+    <form action="/login" method="post">
+        <label>Username
+            <input type="text" placeholder="Enter Username" name="username">
+        </label>
+        <label>Password
+            <input type="password" placeholder="Enter Password" name="password">
+        </label>
+        <button type="submit">Login</button>
+    </form>
+</body>
+</html>"#,
+        ))
+}
+
+/// 校验表单中的账户，成功后写入会话并跳转到管理后台
+/// 失败时无论用户名是否存在都给出同一条提示，避免泄露账户信息
+#[tracing::instrument(
+    name = "Log in",
+    skip(form, pool, session),
+    fields(username = %form.username, user_id = tracing::field::Empty),
+)]
+pub async fn login(
+    form: web::Form<LoginFormData>,
+    pool: web::Data<PgPool>,
+    session: TypedSession,
+) -> impl Responder {
+    let LoginFormData { username, password } = form.into_inner();
+    let credentials = Credentials { username, password };
+    match validate_credentials(credentials, &pool).await {
+        Ok(user_id) => {
+            tracing::Span::current().record("user_id", tracing::field::display(&user_id));
+            session.renew();
+            if let Err(e) = session.insert_user_id(user_id) {
+                tracing::error!("Failed to store the user id in the session: {:?}", e);
+                return HttpResponse::InternalServerError().finish();
+            }
+            see_other("/admin/dashboard")
+        }
+        Err(AuthError::InvalidCredentials) => {
+            FlashMessage::error("Authentication failed.").send();
+            see_other("/login")
+        }
+        Err(AuthError::Unexpected(e)) => {
+            tracing::error!("Failed to validate credentials: {}", e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// 303 重定向，表单提交后浏览器改用 GET 访问目标页
+pub fn see_other(location: &str) -> HttpResponse {
+    HttpResponse::SeeOther()
+        .insert_header((LOCATION, location))
+        .finish()
+}
@@ -1,5 +1,16 @@
+pub mod admin;
+mod error;
 pub mod health;
+pub mod login;
+pub mod newsletters;
 pub mod subscriptions;
+pub mod subscriptions_confirm;
+pub mod subscriptions_unsubscribe;
 
+pub use error::{error_chain_fmt, ProblemDetails};
 pub use health::*;
-pub use subscriptions::*;
\ No newline at end of file
+pub use login::*;
+pub use newsletters::*;
+pub use subscriptions::*;
+pub use subscriptions_confirm::*;
+pub use subscriptions_unsubscribe::*;
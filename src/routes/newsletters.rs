@@ -0,0 +1,176 @@
+use crate::authentication::UserId;
+use crate::idempotency::{save_response, try_processing, IdempotencyKey, NextAction};
+use crate::routes::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpRequest, HttpResponse, ResponseError};
+use sqlx::{PgPool, Postgres, Transaction};
+use chrono::Utc;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct BodyData {
+    pub title: String,
+    pub content: Content,
+}
+
+#[derive(serde::Deserialize)]
+pub struct Content {
+    pub html: String,
+    pub text: String,
+}
+
+/// 发布接口的错误
+#[derive(thiserror::Error)]
+pub enum PublishError {
+    #[error("{0}")]
+    InvalidIdempotencyKey(String),
+    #[error("{context}")]
+    Storage {
+        context: &'static str,
+        #[source]
+        source: sqlx::Error,
+    },
+    #[error("Failed to process the idempotency key.")]
+    Idempotency(#[source] anyhow::Error),
+}
+
+impl PublishError {
+    fn storage(context: &'static str) -> impl FnOnce(sqlx::Error) -> Self {
+        move |source| Self::Storage { context, source }
+    }
+}
+
+impl std::fmt::Debug for PublishError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for PublishError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PublishError::InvalidIdempotencyKey(_) => StatusCode::BAD_REQUEST,
+            PublishError::Storage { .. } | PublishError::Idempotency(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+}
+
+/// 发布一期 newsletter：落库并为每个已确认订阅者入队，由后台 worker 异步投递
+/// 携带 `Idempotency-Key` 头的重复请求不会再次发布，而是返回首次请求的响应
+#[tracing::instrument(
+    name = "Publish a newsletter issue",
+    skip(body, request, pool, user_id),
+    fields(title = %body.title, user_id = %*user_id),
+)]
+pub async fn publish_newsletter(
+    body: web::Json<BodyData>,
+    request: HttpRequest,
+    pool: web::Data<PgPool>,
+    user_id: web::ReqData<UserId>,
+) -> Result<HttpResponse, PublishError> {
+    let user_id = *user_id.into_inner();
+    let idempotency_key = idempotency_key(&request).map_err(PublishError::InvalidIdempotencyKey)?;
+    let mut transaction = match &idempotency_key {
+        Some(idempotency_key) => match try_processing(&pool, idempotency_key, user_id)
+            .await
+            .map_err(PublishError::Idempotency)?
+        {
+            NextAction::StartProcessing(transaction) => transaction,
+            NextAction::ReturnSavedResponse(saved_response) => return Ok(saved_response),
+        },
+        None => pool
+            .begin()
+            .await
+            .map_err(PublishError::storage("Failed to acquire a Postgres connection from the pool."))?,
+    };
+    let issue_id = insert_newsletter_issue(
+        &mut transaction,
+        &body.title,
+        &body.content.text,
+        &body.content.html,
+    )
+        .await
+        .map_err(PublishError::storage("Failed to store newsletter issue details."))?;
+    enqueue_delivery_tasks(&mut transaction, issue_id)
+        .await
+        .map_err(PublishError::storage("Failed to enqueue delivery tasks."))?;
+
+    let response = HttpResponse::Accepted().finish();
+    match idempotency_key {
+        Some(idempotency_key) => save_response(transaction, &idempotency_key, user_id, response)
+            .await
+            .map_err(PublishError::Idempotency),
+        None => {
+            transaction
+                .commit()
+                .await
+                .map_err(PublishError::storage("Failed to commit SQL transaction to publish a newsletter issue."))?;
+            Ok(response)
+        }
+    }
+}
+
+/// 读取可选的 `Idempotency-Key` 请求头
+fn idempotency_key(request: &HttpRequest) -> Result<Option<IdempotencyKey>, String> {
+    let Some(value) = request.headers().get("Idempotency-Key") else {
+        return Ok(None);
+    };
+    let value = value
+        .to_str()
+        .map_err(|_| "The 'Idempotency-Key' header was not a valid UTF8 string.")?;
+    IdempotencyKey::try_from(value.to_string()).map(Some)
+}
+
+#[tracing::instrument(skip_all)]
+async fn insert_newsletter_issue(
+    transaction: &mut Transaction<'_, Postgres>,
+    title: &str,
+    text_content: &str,
+    html_content: &str,
+) -> Result<Uuid, sqlx::Error> {
+    let newsletter_issue_id = Uuid::new_v4();
+    sqlx::query!(
+        r#"
+        INSERT INTO newsletter_issues (
+            newsletter_issue_id,
+            title,
+            text_content,
+            html_content,
+            published_at
+        )
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+        newsletter_issue_id,
+        title,
+        text_content,
+        html_content,
+        Utc::now(),
+    )
+        .execute(&mut **transaction)
+        .await?;
+    Ok(newsletter_issue_id)
+}
+
+#[tracing::instrument(skip_all)]
+async fn enqueue_delivery_tasks(
+    transaction: &mut Transaction<'_, Postgres>,
+    newsletter_issue_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO issue_delivery_queue (
+            newsletter_issue_id,
+            subscriber_email
+        )
+        SELECT $1, email
+        FROM subscriptions
+        WHERE status = 'confirmed'
+        "#,
+        newsletter_issue_id,
+    )
+        .execute(&mut **transaction)
+        .await?;
+    Ok(())
+}
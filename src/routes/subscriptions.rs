@@ -1,55 +1,345 @@
-use actix_web::{web, Responder, HttpResponse};
-use sqlx::PgPool;
+use actix_web::{mime, web, FromRequest, HttpMessage, HttpRequest, HttpResponse, ResponseError};
+use actix_web::dev::Payload;
+use actix_web::error::ErrorUnsupportedMediaType;
+use actix_web::http::header::{Accept, Header};
+use actix_web::http::StatusCode;
+use std::future::{ready, Future};
+use std::pin::Pin;
+use sqlx::{PgPool, Postgres, Transaction};
 use chrono::Utc;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
 use uuid::Uuid;
-use crate::domain::new_subscriber::NewSubscriber;
+use crate::domain::new_subscriber::{FieldError, NewSubscriber, ValidationError};
+use crate::domain::ValidationPolicy;
+use crate::routes::{error_chain_fmt, ProblemDetails};
+use crate::service::email_client::{DeliveryError, EmailSender};
+use crate::startup::ApplicationBaseUrl;
+use crate::suppression::SuppressionList;
+
+/// 订阅接口的错误
+#[derive(thiserror::Error)]
+pub enum SubscribeError {
+    #[error(transparent)]
+    Validation(#[from] ValidationError),
+    #[error("{context}")]
+    Storage {
+        context: &'static str,
+        #[source]
+        source: sqlx::Error,
+    },
+    #[error("Failed to send a confirmation email.")]
+    Delivery(#[from] DeliveryError),
+}
+
+impl SubscribeError {
+    fn storage(context: &'static str) -> impl FnOnce(sqlx::Error) -> Self {
+        move |source| Self::Storage { context, source }
+    }
+}
+
+impl std::fmt::Debug for SubscribeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for SubscribeError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            SubscribeError::Validation(_) => StatusCode::BAD_REQUEST,
+            SubscribeError::Storage { .. } | SubscribeError::Delivery(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self {
+            SubscribeError::Validation(e) => {
+                ProblemDetails::validation(e.to_string(), e.errors.clone()).into_response()
+            }
+            _ => HttpResponse::new(self.status_code()),
+        }
+    }
+}
+
+/// 订阅请求体：按 `Content-Type` 解析 URL 编码表单或 JSON，其他类型返回 415
+pub struct SubscriptionForm(pub FormData);
+
+impl FromRequest for SubscriptionForm {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let essence = req.mime_type().ok().flatten().map(|mime| mime.essence_str().to_owned());
+        match essence.as_deref() {
+            Some("application/x-www-form-urlencoded") => {
+                let form = web::Form::<FormData>::from_request(req, payload);
+                Box::pin(async move { Ok(Self(form.await?.into_inner())) })
+            }
+            Some("application/json") => {
+                let json = web::Json::<FormData>::from_request(req, payload);
+                Box::pin(async move { Ok(Self(json.await?.into_inner())) })
+            }
+            _ => Box::pin(ready(Err(ErrorUnsupportedMediaType(
+                "Expected an application/x-www-form-urlencoded or application/json body.",
+            )))),
+        }
+    }
+}
+
+/// 客户端通过 `Accept: application/json` 请求 JSON 响应时返回的内容
+/// 无论地址此前是否已订阅，响应的结构与状态都相同
+#[derive(serde::Serialize)]
+pub struct SubscriptionResponse {
+    pub subscriber_id: Uuid,  // 新地址为真实 id，已存在的地址每次返回随机 id
+    pub status: &'static str, // 固定为 `accepted`：请求已受理，是否需要确认以邮件为准
+}
 
 #[tracing::instrument(
     name = "Adding a new subscription",
-    skip(form, pool),
+    skip(form, request, pool, email_client, base_url, validation_policy, suppression_list),
     fields(
-        subscriber_name = %form.name,
-        subscriber_email = %form.email,
+        subscriber_name = %form.0.name,
+        subscriber_email = %form.0.email,
     )
 )]
-pub async fn subscribe(form: web::Form<FormData>, pool: web::Data<PgPool>) -> impl Responder {
-    let subscriber = match NewSubscriber::try_from(form.0){
-        Ok(subscriber) => subscriber,
-        Err(e) => return HttpResponse::BadRequest().body(e),
-    };
-    match insert_subscription(&pool, &subscriber).await
+pub async fn subscribe(
+    form: SubscriptionForm,
+    request: HttpRequest,
+    pool: web::Data<PgPool>,
+    email_client: web::Data<dyn EmailSender>,
+    base_url: web::Data<ApplicationBaseUrl>,
+    validation_policy: web::Data<ValidationPolicy>,
+    suppression_list: web::Data<SuppressionList>,
+) -> Result<HttpResponse, SubscribeError> {
+    let subscriber = NewSubscriber::parse(form.0, &validation_policy)?;
+    if let Some(reason) = suppression_list
+        .check(pool.get_ref(), subscriber.email.as_ref())
+        .await
+        .map_err(SubscribeError::storage("Failed to check the email against the suppression list."))?
     {
-        Ok(_) => HttpResponse::Ok().finish(),
-        Err(_) => HttpResponse::InternalServerError().finish()
+        return Err(ValidationError {
+            errors: vec![FieldError {
+                field: "email",
+                code: reason.code(),
+                message: reason.to_string(),
+            }],
+        }
+        .into());
+    }
+    // 订阅者与确认令牌在同一事务中写入，邮件发送失败时整体回滚
+    let mut transaction = pool
+        .begin()
+        .await
+        .map_err(SubscribeError::storage("Failed to acquire a Postgres connection from the pool."))?;
+    // 已确认的地址与新地址返回相同的响应，避免借此探测邮箱是否已订阅
+    let registration = register_subscriber(&mut transaction, &subscriber)
+        .await
+        .map_err(SubscribeError::storage("Failed to register the subscriber in the database."))?;
+    if let Registration::Created(subscriber_id) | Registration::AwaitingConfirmation(subscriber_id) = registration {
+        let subscription_token = generate_subscription_token();
+        store_token(&mut transaction, subscriber_id, &subscription_token)
+            .await
+            .map_err(SubscribeError::storage("Failed to store the confirmation token for a new subscriber."))?;
+        send_confirmation_email(email_client.get_ref(), &subscriber, &base_url.0, &subscription_token).await?;
+    }
+    transaction
+        .commit()
+        .await
+        .map_err(SubscribeError::storage("Failed to commit SQL transaction to store a new subscriber."))?;
+
+    // 只比较类型本身，忽略 `charset` 等参数
+    let accepts_json = Accept::parse(&request)
+        .is_ok_and(|accept| accept.preference().essence_str() == mime::APPLICATION_JSON.essence_str());
+    if accepts_json {
+        // 已存在的地址若返回库中的 id，重复提交时 id 不变，可据此判断地址是否已注册
+        let subscriber_id = match registration {
+            Registration::Created(subscriber_id) => subscriber_id,
+            _ => Uuid::new_v4(),
+        };
+        Ok(HttpResponse::Ok().json(SubscriptionResponse {
+            subscriber_id,
+            status: "accepted",
+        }))
+    } else {
+        Ok(HttpResponse::Ok().finish())
     }
 }
 
+/// 写入订阅者后的处理方式
+#[derive(Debug, Clone, Copy)]
+pub enum Registration {
+    /// 新地址：发送确认邮件
+    Created(Uuid),
+    /// 仍待确认的地址或已退订后重新订阅的地址：重新发送确认邮件
+    AwaitingConfirmation(Uuid),
+    /// 已确认的地址：静默成功，不再发送邮件
+    AlreadyConfirmed,
+}
+
+/// 写入新订阅者；邮箱已存在时按其当前状态处理
+/// 并发的同地址请求会在 `ON CONFLICT` 上等待先到的事务结束，随后 `FOR UPDATE` 锁住已有记录
+#[tracing::instrument(
+    name = "Register a subscriber",
+    skip(transaction, subscriber),
+)]
+pub async fn register_subscriber(
+    transaction: &mut Transaction<'_, Postgres>,
+    subscriber: &NewSubscriber,
+) -> Result<Registration, sqlx::Error> {
+    if let Some(subscriber_id) = insert_subscription(transaction, subscriber).await? {
+        return Ok(Registration::Created(subscriber_id));
+    }
+
+    let existing = sqlx::query!(
+        r#"
+        SELECT id, status
+        FROM subscriptions
+        WHERE lower(email) = lower($1)
+        FOR UPDATE
+        "#,
+        subscriber.email.as_ref(),
+    )
+        .fetch_one(&mut **transaction)
+        .await?;
+    match existing.status.as_str() {
+        "confirmed" => Ok(Registration::AlreadyConfirmed),
+        "unsubscribed" => {
+            resubscribe(transaction, existing.id, subscriber).await?;
+            Ok(Registration::AwaitingConfirmation(existing.id))
+        }
+        _ => Ok(Registration::AwaitingConfirmation(existing.id)),
+    }
+}
+
+/// 插入待确认的订阅者，邮箱已存在时返回 `None`
 #[tracing::instrument(
     name = "Saving new subscriber details in the database",
-    skip(pool, subscriber),
+    skip(transaction, subscriber),
 )]
-pub async fn insert_subscription(pool: &PgPool, subscriber: &NewSubscriber) -> Result<(), sqlx::Error> {
-    sqlx::query!(
+pub async fn insert_subscription(
+    transaction: &mut Transaction<'_, Postgres>,
+    subscriber: &NewSubscriber,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let subscriber_id = Uuid::new_v4();
+    let result = sqlx::query!(
         r#"
-        INSERT INTO subscriptions (id, email, name, subscribed_at)
-        VALUES ($1, $2, $3, $4)
+        INSERT INTO subscriptions (id, email, name, subscribed_at, status)
+        VALUES ($1, $2, $3, $4, 'pending_confirmation')
+        ON CONFLICT ((lower(email))) DO NOTHING
         "#,
-        Uuid::new_v4(),
+        subscriber_id,
         subscriber.email.as_ref(),
         subscriber.name.as_ref(),
         Utc::now(),
     )
-        .execute(pool)
-        .await
-        .map_err( |e| {
-            tracing::error!("Failed to execute query: {:?}", e);
-            e
-        })?;
+        .execute(&mut **transaction)
+        .await?;
+    Ok((result.rows_affected() > 0).then_some(subscriber_id))
+}
+
+/// 已退订的地址重新订阅：回到待确认状态，需要再次确认
+/// 旧的确认令牌一并删除，只有新发送的确认链接有效
+#[tracing::instrument(
+    name = "Re-opt-in an unsubscribed subscriber",
+    skip(transaction, subscriber),
+)]
+async fn resubscribe(
+    transaction: &mut Transaction<'_, Postgres>,
+    subscriber_id: Uuid,
+    subscriber: &NewSubscriber,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE subscriptions
+        SET
+            name = $2,
+            subscribed_at = $3,
+            status = 'pending_confirmation',
+            unsubscribed_at = NULL
+        WHERE id = $1
+        "#,
+        subscriber_id,
+        subscriber.name.as_ref(),
+        Utc::now(),
+    )
+        .execute(&mut **transaction)
+        .await?;
+    sqlx::query!(
+        r#"DELETE FROM subscription_tokens WHERE subscriber_id = $1"#,
+        subscriber_id,
+    )
+        .execute(&mut **transaction)
+        .await?;
     Ok(())
 }
 
+#[tracing::instrument(
+    name = "Store subscription token in the database",
+    skip(transaction, subscription_token),
+)]
+pub async fn store_token(
+    transaction: &mut Transaction<'_, Postgres>,
+    subscriber_id: Uuid,
+    subscription_token: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO subscription_tokens (subscription_token, subscriber_id)
+        VALUES ($1, $2)
+        "#,
+        subscription_token,
+        subscriber_id,
+    )
+        .execute(&mut **transaction)
+        .await?;
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Send a confirmation email to a new subscriber",
+    skip(email_client, subscriber, base_url, subscription_token),
+)]
+pub async fn send_confirmation_email(
+    email_client: &dyn EmailSender,
+    subscriber: &NewSubscriber,
+    base_url: &str,
+    subscription_token: &str,
+) -> Result<(), DeliveryError> {
+    let confirmation_link = format!(
+        "{}/subscriptions/confirm?subscription_token={}",
+        base_url, subscription_token
+    );
+    let html_body = format!(
+        "Welcome to our newsletter!<br />\
+        Click <a href=\"{}\">here</a> to confirm your subscription.",
+        confirmation_link
+    );
+    let plain_body = format!(
+        "Welcome to our newsletter!\nVisit {} to confirm your subscription.",
+        confirmation_link
+    );
+    email_client
+        .send(&subscriber.email, "Welcome!", &html_body, &plain_body)
+        .await
+}
+
+/// 生成 25 位大小写敏感的随机字母数字令牌
+fn generate_subscription_token() -> String {
+    let mut rng = thread_rng();
+    std::iter::repeat_with(|| rng.sample(Alphanumeric))
+        .map(char::from)
+        .take(25)
+        .collect()
+}
+
+/// 缺失的字段按空字符串处理，与空值一样报告为 `empty`
 #[derive(serde::Deserialize)]
 pub struct FormData {
+    #[serde(default)]
     pub name: String,
+    #[serde(default)]
     pub email: String,
-}
\ No newline at end of file
+}
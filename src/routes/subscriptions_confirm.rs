@@ -0,0 +1,94 @@
+use crate::routes::error_chain_fmt;
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpResponse, ResponseError};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct Parameters {
+    pub subscription_token: String,
+}
+
+/// 确认订阅接口的错误
+#[derive(thiserror::Error)]
+pub enum ConfirmError {
+    #[error("There is no subscriber associated with the provided token.")]
+    UnknownToken,
+    #[error("{context}")]
+    Storage {
+        context: &'static str,
+        #[source]
+        source: sqlx::Error,
+    },
+}
+
+impl ConfirmError {
+    fn storage(context: &'static str) -> impl FnOnce(sqlx::Error) -> Self {
+        move |source| Self::Storage { context, source }
+    }
+}
+
+impl std::fmt::Debug for ConfirmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for ConfirmError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ConfirmError::UnknownToken => StatusCode::UNAUTHORIZED,
+            ConfirmError::Storage { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[tracing::instrument(
+    name = "Confirm a pending subscriber",
+    skip(parameters, pool),
+)]
+pub async fn confirm(
+    parameters: web::Query<Parameters>,
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse, ConfirmError> {
+    let subscriber_id = get_subscriber_id_from_token(&pool, &parameters.subscription_token)
+        .await
+        .map_err(ConfirmError::storage("Failed to retrieve the subscriber id associated with the provided token."))?
+        .ok_or(ConfirmError::UnknownToken)?;
+    confirm_subscriber(&pool, subscriber_id)
+        .await
+        .map_err(ConfirmError::storage("Failed to update the subscriber status to `confirmed`."))?;
+    Ok(HttpResponse::Ok().finish())
+}
+
+#[tracing::instrument(
+    name = "Mark subscriber as confirmed",
+    skip(pool),
+)]
+pub async fn confirm_subscriber(pool: &PgPool, subscriber_id: Uuid) -> Result<(), sqlx::Error> {
+    // 只确认待确认的订阅者：已退订的地址不能凭旧的确认链接恢复订阅
+    sqlx::query!(
+        r#"UPDATE subscriptions SET status = 'confirmed' WHERE id = $1 AND status = 'pending_confirmation'"#,
+        subscriber_id,
+    )
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+#[tracing::instrument(
+    name = "Get subscriber_id from token",
+    skip(pool, subscription_token),
+)]
+pub async fn get_subscriber_id_from_token(
+    pool: &PgPool,
+    subscription_token: &str,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"SELECT subscriber_id FROM subscription_tokens WHERE subscription_token = $1"#,
+        subscription_token,
+    )
+        .fetch_optional(pool)
+        .await?;
+    Ok(result.map(|r| r.subscriber_id))
+}
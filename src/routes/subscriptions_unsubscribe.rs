@@ -0,0 +1,114 @@
+use crate::domain::UnsubscribeToken;
+use crate::startup::HmacSecret;
+use crate::routes::error_chain_fmt;
+use actix_web::http::header::ContentType;
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpResponse, Responder, ResponseError};
+use secrecy::ExposeSecret;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+#[derive(serde::Deserialize)]
+pub struct UnsubscribeParameters {
+    pub token: String,
+}
+
+/// 退订确认页：GET 不修改状态，避免邮件扫描器预取链接时误退订
+#[tracing::instrument(name = "Show the unsubscribe form", skip(parameters, hmac_secret))]
+pub async fn unsubscribe_form(
+    parameters: web::Query<UnsubscribeParameters>,
+    hmac_secret: web::Data<HmacSecret>,
+) -> impl Responder {
+    if UnsubscribeToken::verify(&parameters.token, hmac_secret.0.expose_secret().as_bytes()).is_err() {
+        return HttpResponse::Unauthorized().finish();
+    }
+    HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .body(format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta http-equiv="content-type" content="text/html; charset=utf-8">
+    <title>Unsubscribe</title>
+</head>
+<body>
+    <p>Do you want to stop receiving our newsletter?</p>
+    <form action="/subscriptions/unsubscribe?token={}" method="post">
+        <input type="hidden" name="List-Unsubscribe" value="One-Click">
+        <button type="submit">Unsubscribe</button>
+    </form>
+</body>
+</html>"#,
+            htmlescape::encode_attribute(&parameters.token),
+        ))
+}
+
+/// 退订接口的错误
+#[derive(thiserror::Error)]
+pub enum UnsubscribeError {
+    #[error("{0}")]
+    InvalidToken(String),
+    #[error("There is no subscriber associated with the provided token.")]
+    UnknownSubscriber,
+    #[error("Failed to mark the subscriber as unsubscribed.")]
+    Storage(#[source] sqlx::Error),
+}
+
+impl std::fmt::Debug for UnsubscribeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        error_chain_fmt(self, f)
+    }
+}
+
+impl ResponseError for UnsubscribeError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            // 签名有效但订阅者已被删除时同样视为无效令牌
+            UnsubscribeError::InvalidToken(_) | UnsubscribeError::UnknownSubscriber => {
+                StatusCode::UNAUTHORIZED
+            }
+            UnsubscribeError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// 执行退订：既处理确认页上的按钮，也处理邮件客户端按 RFC 8058 发起的一键退订 POST
+/// 重复退订直接返回成功
+#[tracing::instrument(name = "Unsubscribe a subscriber", skip(parameters, pool, hmac_secret))]
+pub async fn unsubscribe(
+    parameters: web::Query<UnsubscribeParameters>,
+    pool: web::Data<PgPool>,
+    hmac_secret: web::Data<HmacSecret>,
+) -> Result<HttpResponse, UnsubscribeError> {
+    let subscriber_id = UnsubscribeToken::verify(&parameters.token, hmac_secret.0.expose_secret().as_bytes())
+        .map_err(UnsubscribeError::InvalidToken)?;
+    if !mark_subscriber_as_unsubscribed(&pool, subscriber_id)
+        .await
+        .map_err(UnsubscribeError::Storage)?
+    {
+        return Err(UnsubscribeError::UnknownSubscriber);
+    }
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .body("<p>You have been unsubscribed.</p>"))
+}
+
+#[tracing::instrument(
+    name = "Mark subscriber as unsubscribed",
+    skip(pool),
+)]
+pub async fn mark_subscriber_as_unsubscribed(pool: &PgPool, subscriber_id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE subscriptions
+        SET
+            status = 'unsubscribed',
+            unsubscribed_at = COALESCE(unsubscribed_at, now())
+        WHERE id = $1
+        "#,
+        subscriber_id,
+    )
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,65 @@
+use super::{DeliveryError, EmailContent, EmailSender, build_message};
+use crate::domain::SubscriberEmail;
+use chrono::Utc;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// 将邮件以 `.eml` 文件写入本地目录，便于本地开发时查看
+pub struct FileEmailClient {
+    output_dir: PathBuf,
+    sender: SubscriberEmail,
+}
+
+impl FileEmailClient {
+    pub fn new(output_dir: impl Into<PathBuf>, sender: SubscriberEmail) -> Self {
+        Self { output_dir: output_dir.into(), sender }
+    }
+}
+
+#[async_trait::async_trait]
+impl EmailSender for FileEmailClient {
+    async fn send_email(
+        &self,
+        recipient: &SubscriberEmail,
+        content: &EmailContent,
+    ) -> Result<(), DeliveryError> {
+        let email = build_message(&self.sender, recipient, content)?;
+
+        // 文件名：时间戳 + 随机 id，保证按时间排序且不冲突
+        let file_name = format!("{}-{}.eml", Utc::now().format("%Y%m%dT%H%M%S%.3f"), Uuid::new_v4());
+        let output_dir = self.output_dir.clone();
+        tokio::task::spawn_blocking(move || {
+            std::fs::create_dir_all(&output_dir)?;
+            std::fs::write(output_dir.join(file_name), email.formatted())
+        })
+            .await
+            .map_err(|e| DeliveryError::Transient(format!("Task join error: {}", e)))?
+            .map_err(|e| DeliveryError::Transient(format!("Failed to write email file: {}", e)))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fake::{Fake, faker::internet::en::SafeEmail};
+
+    #[tokio::test]
+    async fn send_email_writes_an_eml_file_to_the_output_dir() {
+        let output_dir = std::env::temp_dir().join(Uuid::new_v4().to_string());
+        let sender = SubscriberEmail::parse(SafeEmail().fake()).unwrap();
+        let recipient = SubscriberEmail::parse(SafeEmail().fake()).unwrap();
+        let client = FileEmailClient::new(output_dir.clone(), sender);
+
+        client
+            .send(&recipient, "Subject", "<p>Hello</p>", "Hello")
+            .await
+            .expect("Failed to write email");
+
+        let files: Vec<_> = std::fs::read_dir(&output_dir).unwrap().collect();
+        assert_eq!(files.len(), 1);
+        let content = std::fs::read_to_string(files[0].as_ref().unwrap().path()).unwrap();
+        assert!(content.contains(recipient.as_ref()));
+        std::fs::remove_dir_all(output_dir).unwrap();
+    }
+}
@@ -0,0 +1,365 @@
+use super::{DeliveryError, EmailContent, EmailSender, LIST_UNSUBSCRIBE_POST};
+use crate::domain::SubscriberEmail;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use reqwest::{Client, StatusCode};
+use secrecy::{ExposeSecret, SecretString};
+use std::time::Duration;
+
+/// 基于 JSON HTTP API 的发送后端（Postmark 风格）
+pub struct HttpEmailClient {
+    http_client: Client,
+    base_url: String,
+    api_token: SecretString,
+    sender: SubscriberEmail,
+}
+
+impl HttpEmailClient {
+    pub fn new(
+        base_url: String,
+        api_token: SecretString,
+        sender: SubscriberEmail,
+        timeout: Duration,
+    ) -> Self {
+        let http_client = Client::builder()
+            .timeout(timeout)
+            .build()
+            .expect("failed to build HTTP client");
+        Self { http_client, base_url, api_token, sender }
+    }
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct SendEmailRequest<'a> {
+    from: &'a str,
+    to: &'a str,
+    subject: &'a str,
+    html_body: &'a str,
+    text_body: &'a str,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    headers: Vec<HeaderRequest<'a>>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    attachments: Vec<AttachmentRequest<'a>>,
+}
+
+/// 自定义邮件头
+#[derive(serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct HeaderRequest<'a> {
+    name: &'a str,
+    value: String,
+}
+
+/// 附件与内嵌图片共用同一结构，内嵌图片额外携带 `ContentID`
+#[derive(serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct AttachmentRequest<'a> {
+    name: &'a str,
+    content: String, // base64 编码
+    content_type: &'a str,
+    #[serde(rename = "ContentID", skip_serializing_if = "Option::is_none")]
+    content_id: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl EmailSender for HttpEmailClient {
+    async fn send_email(
+        &self,
+        recipient: &SubscriberEmail,
+        content: &EmailContent,
+    ) -> Result<(), DeliveryError> {
+        let url = format!("{}/email", self.base_url);
+        let inline_images = content.inline_images.iter().map(|image| AttachmentRequest {
+            name: &image.content_id,
+            content: STANDARD.encode(&image.data),
+            content_type: &image.content_type,
+            content_id: Some(format!("cid:{}", image.content_id)),
+        });
+        let attachments = content.attachments.iter().map(|attachment| AttachmentRequest {
+            name: &attachment.filename,
+            content: STANDARD.encode(&attachment.data),
+            content_type: &attachment.content_type,
+            content_id: None,
+        });
+        let headers = match &content.list_unsubscribe {
+            Some(url) => vec![
+                HeaderRequest { name: "List-Unsubscribe", value: format!("<{}>", url) },
+                HeaderRequest { name: "List-Unsubscribe-Post", value: LIST_UNSUBSCRIBE_POST.to_string() },
+            ],
+            None => Vec::new(),
+        };
+        let request_body = SendEmailRequest {
+            from: self.sender.as_ref(),
+            to: recipient.as_ref(),
+            subject: &content.subject,
+            html_body: &content.html,
+            text_body: &content.text,
+            headers,
+            attachments: inline_images.chain(attachments).collect(),
+        };
+        self.http_client
+            .post(&url)
+            .header("X-Postmark-Server-Token", self.api_token.expose_secret())
+            .json(&request_body)
+            .send()
+            .await
+            .map_err(|e| DeliveryError::Transient(format!("HTTP send error: {}", e)))?
+            .error_for_status()
+            .map_err(classify_status_error)?;
+        Ok(())
+    }
+
+    /// 对 API 地址发送 HEAD 请求，沿用发送邮件的超时；收到非 5xx 响应即视为可达
+    async fn check_connection(&self) -> Option<Result<(), String>> {
+        let outcome = match self.http_client.head(&self.base_url).send().await {
+            Ok(response) if response.status().is_server_error() => {
+                Err(format!("Email API responded with {}", response.status()))
+            }
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Email API is unreachable: {}", e)),
+        };
+        Some(outcome)
+    }
+}
+
+/// 429 与 5xx 可重试，其余 4xx 说明请求本身有误
+fn classify_status_error(e: reqwest::Error) -> DeliveryError {
+    let message = format!("Email API error: {}", e);
+    match e.status() {
+        Some(status) if status.is_client_error() && status != StatusCode::TOO_MANY_REQUESTS => {
+            DeliveryError::Permanent(message)
+        }
+        _ => DeliveryError::Transient(message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::email_client::{EmailAttachment, InlineImage};
+    use claim::{assert_err, assert_ok};
+    use fake::faker::internet::en::SafeEmail;
+    use fake::faker::lorem::en::{Paragraph, Sentence};
+    use fake::{Fake, Faker};
+    use wiremock::matchers::{any, header, header_exists, method, path};
+    use wiremock::{Mock, MockServer, Request, ResponseTemplate};
+
+    struct SendEmailBodyMatcher;
+
+    impl wiremock::Match for SendEmailBodyMatcher {
+        fn matches(&self, request: &Request) -> bool {
+            let result: Result<serde_json::Value, _> = serde_json::from_slice(&request.body);
+            if let Ok(body) = result {
+                body.get("From").is_some()
+                    && body.get("To").is_some()
+                    && body.get("Subject").is_some()
+                    && body.get("HtmlBody").is_some()
+                    && body.get("TextBody").is_some()
+            } else {
+                false
+            }
+        }
+    }
+
+    fn subject() -> String {
+        Sentence(1..2).fake()
+    }
+
+    fn content() -> String {
+        Paragraph(1..10).fake()
+    }
+
+    fn email() -> SubscriberEmail {
+        SubscriberEmail::parse(SafeEmail().fake()).unwrap()
+    }
+
+    fn email_client(base_url: String) -> HttpEmailClient {
+        HttpEmailClient::new(base_url, SecretString::from(Faker.fake::<String>()), email(), Duration::from_millis(200))
+    }
+
+    #[tokio::test]
+    async fn send_email_sends_the_expected_request() {
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+
+        Mock::given(header_exists("X-Postmark-Server-Token"))
+            .and(header("Content-Type", "application/json"))
+            .and(path("/email"))
+            .and(method("POST"))
+            .and(SendEmailBodyMatcher)
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let _ = email_client
+            .send(&email(), &subject(), &content(), &content())
+            .await;
+    }
+
+    #[tokio::test]
+    async fn attachments_and_inline_images_are_sent_base64_encoded() {
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let mut content = EmailContent::new(subject(), content(), content());
+        content.inline_images.push(InlineImage {
+            content_id: "logo".into(),
+            content_type: "image/png".into(),
+            data: b"png".to_vec(),
+        });
+        content.attachments.push(EmailAttachment {
+            filename: "report.pdf".into(),
+            content_type: "application/pdf".into(),
+            data: b"pdf".to_vec(),
+        });
+        assert_ok!(email_client.send_email(&email(), &content).await);
+
+        let request = &mock_server.received_requests().await.unwrap()[0];
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        let attachments = body["Attachments"].as_array().unwrap();
+        assert_eq!(attachments.len(), 2);
+        assert_eq!(attachments[0]["ContentID"], "cid:logo");
+        assert_eq!(attachments[0]["Content"], STANDARD.encode(b"png"));
+        assert_eq!(attachments[1]["Name"], "report.pdf");
+        assert!(attachments[1].get("ContentID").is_none());
+    }
+
+    #[tokio::test]
+    async fn send_bulk_adds_one_click_unsubscribe_headers() {
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let unsubscribe_url = "https://example.com/subscriptions/unsubscribe?token=abc";
+        assert_ok!(
+            email_client
+                .send_bulk(&email(), &subject(), &content(), &content(), unsubscribe_url)
+                .await
+        );
+
+        let request = &mock_server.received_requests().await.unwrap()[0];
+        let body: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(
+            body["Headers"],
+            serde_json::json!([
+                { "Name": "List-Unsubscribe", "Value": format!("<{}>", unsubscribe_url) },
+                { "Name": "List-Unsubscribe-Post", "Value": "List-Unsubscribe=One-Click" },
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn send_email_succeeds_if_the_server_returns_200() {
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let outcome = email_client
+            .send(&email(), &subject(), &content(), &content())
+            .await;
+
+        assert_ok!(outcome);
+    }
+
+    #[tokio::test]
+    async fn send_email_fails_if_the_server_returns_500() {
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(500))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let outcome = email_client
+            .send(&email(), &subject(), &content(), &content())
+            .await;
+
+        assert!(outcome.unwrap_err().is_transient());
+    }
+
+    #[tokio::test]
+    async fn send_email_fails_permanently_if_the_server_rejects_the_request() {
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+
+        Mock::given(any())
+            .respond_with(ResponseTemplate::new(422))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let outcome = email_client
+            .send(&email(), &subject(), &content(), &content())
+            .await;
+
+        assert!(!outcome.unwrap_err().is_transient());
+    }
+
+    #[tokio::test]
+    async fn send_email_times_out_if_the_server_takes_too_long() {
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+
+        let response = ResponseTemplate::new(200).set_delay(Duration::from_secs(180));
+        Mock::given(any())
+            .respond_with(response)
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let outcome = email_client
+            .send(&email(), &subject(), &content(), &content())
+            .await;
+
+        assert_err!(outcome);
+    }
+
+    #[tokio::test]
+    async fn check_connection_succeeds_when_the_api_responds() {
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        assert_ok!(email_client.check_connection().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn check_connection_fails_on_server_errors_and_timeouts() {
+        let mock_server = MockServer::start().await;
+        let email_client = email_client(mock_server.uri());
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(180)))
+            .mount(&mock_server)
+            .await;
+
+        assert_err!(email_client.check_connection().await.unwrap());
+        assert_err!(email_client.check_connection().await.unwrap());
+    }
+}
@@ -0,0 +1,339 @@
+mod file;
+mod http;
+mod smtp;
+
+pub use file::FileEmailClient;
+pub use http::HttpEmailClient;
+pub use smtp::{SmtpEmailClient, SmtpPoolOptions};
+
+use crate::configuration::{EmailClientKind, EmailClientSettings};
+use crate::domain::SubscriberEmail;
+use lettre::Message;
+use lettre::message::header::{ContentType, Header, HeaderName, HeaderValue};
+use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use secrecy::ExposeSecret;
+use std::sync::Arc;
+
+/// 邮件附件
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+    pub filename: String,
+    pub content_type: String, // 如 application/pdf
+    pub data: Vec<u8>,
+}
+
+/// 内嵌图片，HTML 中通过 `cid:{content_id}` 引用
+#[derive(Debug, Clone)]
+pub struct InlineImage {
+    pub content_id: String,
+    pub content_type: String, // 如 image/png
+    pub data: Vec<u8>,
+}
+
+/// 一封邮件的内容：纯文本与 HTML 两种视图，以及可选的内嵌图片与附件
+#[derive(Debug, Clone, Default)]
+pub struct EmailContent {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+    pub inline_images: Vec<InlineImage>,
+    pub attachments: Vec<EmailAttachment>,
+    pub list_unsubscribe: Option<String>, // 退订链接，设置后附带 List-Unsubscribe 相关邮件头
+}
+
+impl EmailContent {
+    pub fn new(subject: impl Into<String>, html: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            subject: subject.into(),
+            html: html.into(),
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// 投递失败的分类，决定投递队列是否重试
+#[derive(Debug, thiserror::Error)]
+pub enum DeliveryError {
+    /// 临时性失败：SMTP 4xx、连接/超时错误、HTTP 429 与 5xx，稍后重试可能成功
+    #[error("transient delivery failure: {0}")]
+    Transient(String),
+    /// 永久性失败：SMTP 5xx、HTTP 其余 4xx、邮件无法构建，重试没有意义
+    #[error("permanent delivery failure: {0}")]
+    Permanent(String),
+}
+
+impl DeliveryError {
+    pub fn is_transient(&self) -> bool {
+        matches!(self, DeliveryError::Transient(_))
+    }
+}
+
+/// 邮件发送的统一抽象，具体后端由 `EmailClientSettings::kind` 决定
+#[async_trait::async_trait]
+pub trait EmailSender: Send + Sync {
+    /// 发送完整内容的邮件
+    async fn send_email(
+        &self,
+        recipient: &SubscriberEmail,
+        content: &EmailContent,
+    ) -> Result<(), DeliveryError>;
+
+    /// 仅包含文本与 HTML 正文的便捷发送
+    async fn send(
+        &self,
+        recipient: &SubscriberEmail,
+        subject: &str,
+        html_content: &str,
+        text_content: &str,
+    ) -> Result<(), DeliveryError> {
+        let content = EmailContent::new(subject, html_content, text_content);
+        self.send_email(recipient, &content).await
+    }
+
+    /// 群发邮件：附带 `List-Unsubscribe` 与 RFC 8058 一键退订头，邮件客户端据此显示退订按钮
+    async fn send_bulk(
+        &self,
+        recipient: &SubscriberEmail,
+        subject: &str,
+        html_content: &str,
+        text_content: &str,
+        unsubscribe_url: &str,
+    ) -> Result<(), DeliveryError> {
+        let mut content = EmailContent::new(subject, html_content, text_content);
+        content.list_unsubscribe = Some(unsubscribe_url.to_string());
+        self.send_email(recipient, &content).await
+    }
+
+    /// 检查后端是否可达，供就绪检查使用；返回 `None` 表示该后端没有可检查的远端
+    async fn check_connection(&self) -> Option<Result<(), String>> {
+        None
+    }
+}
+
+/// `List-Unsubscribe-Post` 头的固定取值
+pub const LIST_UNSUBSCRIBE_POST: &str = "List-Unsubscribe=One-Click";
+
+/// RFC 2369 `List-Unsubscribe: <url>`
+#[derive(Clone)]
+struct ListUnsubscribe(String);
+
+impl Header for ListUnsubscribe {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe")
+    }
+
+    fn parse(s: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self(s.trim().trim_start_matches('<').trim_end_matches('>').to_string()))
+    }
+
+    fn display(&self) -> HeaderValue {
+        HeaderValue::new(Self::name(), format!("<{}>", self.0))
+    }
+}
+
+/// RFC 8058 `List-Unsubscribe-Post: List-Unsubscribe=One-Click`
+#[derive(Clone)]
+struct ListUnsubscribePost;
+
+impl Header for ListUnsubscribePost {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe-Post")
+    }
+
+    fn parse(_s: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self)
+    }
+
+    fn display(&self) -> HeaderValue {
+        HeaderValue::new(Self::name(), LIST_UNSUBSCRIBE_POST.to_string())
+    }
+}
+
+/// 按配置构建邮件发送后端
+pub fn build_email_client(settings: EmailClientSettings) -> Result<Arc<dyn EmailSender>, String> {
+    let sender = settings.sender()?;
+    let client: Arc<dyn EmailSender> = match settings.kind {
+        EmailClientKind::Smtp => {
+            let pool_options = SmtpPoolOptions {
+                max_connections: settings.smtp_pool_max_connections,
+                idle_timeout: settings.smtp_pool_idle_timeout(),
+                request_timeout: settings.timeout(),
+            };
+            Arc::new(SmtpEmailClient::new(
+                settings.smtp_host,
+                settings.smtp_port,
+                Credentials::new(settings.smtp_username, settings.smtp_password.expose_secret().to_owned()),
+                settings.use_starttls,
+                pool_options,
+                sender,
+            ))
+        }
+        EmailClientKind::Http => {
+            let timeout = settings.timeout();
+            Arc::new(HttpEmailClient::new(
+                settings.api_base_url,
+                settings.api_token,
+                sender,
+                timeout,
+            ))
+        }
+        EmailClientKind::File => Arc::new(FileEmailClient::new(settings.output_dir, sender)),
+    };
+    Ok(client)
+}
+
+/// 构建 SMTP 与文件后端共用的 MIME 邮件
+/// 结构：mixed(alternative(text, related(html, 内嵌图片...)), 附件...)，
+/// 没有内嵌图片或附件时省略对应层级
+fn build_message(
+    sender: &SubscriberEmail,
+    recipient: &SubscriberEmail,
+    content: &EmailContent,
+) -> Result<Message, DeliveryError> {
+    let from: Mailbox = sender
+        .as_ref()
+        .parse()
+        .map_err(|e| DeliveryError::Permanent(format!("Invalid sender email: {}", e)))?;
+    let to: Mailbox = recipient
+        .as_ref()
+        .parse()
+        .map_err(|e| DeliveryError::Permanent(format!("Invalid recipient email: {}", e)))?;
+
+    let text_part = SinglePart::plain(content.text.clone());
+    let html_part = SinglePart::html(content.html.clone());
+    let alternative = if content.inline_images.is_empty() {
+        MultiPart::alternative()
+            .singlepart(text_part)
+            .singlepart(html_part)
+    } else {
+        let mut related = MultiPart::related().singlepart(html_part);
+        for image in &content.inline_images {
+            related = related.singlepart(
+                Attachment::new_inline(image.content_id.clone())
+                    .body(image.data.clone(), parse_content_type(&image.content_type)?),
+            );
+        }
+        MultiPart::alternative()
+            .singlepart(text_part)
+            .multipart(related)
+    };
+
+    let body = if content.attachments.is_empty() {
+        alternative
+    } else {
+        let mut mixed = MultiPart::mixed().multipart(alternative);
+        for attachment in &content.attachments {
+            mixed = mixed.singlepart(
+                Attachment::new(attachment.filename.clone())
+                    .body(attachment.data.clone(), parse_content_type(&attachment.content_type)?),
+            );
+        }
+        mixed
+    };
+
+    let mut builder = Message::builder()
+        .from(from)
+        .to(to)
+        .subject(content.subject.as_str());
+    if let Some(url) = &content.list_unsubscribe {
+        builder = builder
+            .header(ListUnsubscribe(url.clone()))
+            .header(ListUnsubscribePost);
+    }
+    builder
+        .multipart(body)
+        .map_err(|e| DeliveryError::Permanent(format!("Failed to build email: {}", e)))
+}
+
+fn parse_content_type(content_type: &str) -> Result<ContentType, DeliveryError> {
+    ContentType::parse(content_type).map_err(|e| {
+        DeliveryError::Permanent(format!("Invalid content type {}: {}", content_type, e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fake::{Fake, faker::internet::en::SafeEmail};
+
+    fn email() -> SubscriberEmail {
+        SubscriberEmail::parse(SafeEmail().fake()).unwrap()
+    }
+
+    fn formatted(content: &EmailContent) -> String {
+        let message = build_message(&email(), &email(), content).unwrap();
+        String::from_utf8(message.formatted()).unwrap()
+    }
+
+    #[test]
+    fn plain_emails_are_multipart_alternative_with_utf8_parts() {
+        let content = EmailContent::new("Subject", "<p>你好</p>", "你好");
+
+        let message = formatted(&content);
+
+        assert!(message.contains("multipart/alternative"));
+        assert!(message.contains("text/plain; charset=utf-8"));
+        assert!(message.contains("text/html; charset=utf-8"));
+        assert!(!message.contains("multipart/mixed"));
+        assert!(!message.contains("multipart/related"));
+    }
+
+    #[test]
+    fn bulk_emails_carry_one_click_unsubscribe_headers() {
+        let mut content = EmailContent::new("Subject", "<p>Hi</p>", "Hi");
+        content.list_unsubscribe = Some("https://example.com/subscriptions/unsubscribe?token=abc".into());
+
+        let message = formatted(&content);
+
+        assert!(message.contains("List-Unsubscribe: <https://example.com/subscriptions/unsubscribe?token=abc>"));
+        assert!(message.contains("List-Unsubscribe-Post: List-Unsubscribe=One-Click"));
+        assert!(!formatted(&EmailContent::new("Subject", "<p>Hi</p>", "Hi")).contains("List-Unsubscribe"));
+    }
+
+    #[test]
+    fn inline_images_are_wrapped_with_the_html_part_in_multipart_related() {
+        let mut content = EmailContent::new("Subject", "<img src=\"cid:logo\" />", "logo");
+        content.inline_images.push(InlineImage {
+            content_id: "logo".into(),
+            content_type: "image/png".into(),
+            data: vec![0x89, 0x50, 0x4e, 0x47],
+        });
+
+        let message = formatted(&content);
+
+        assert!(message.contains("multipart/related"));
+        assert!(message.contains("Content-ID: <logo>"));
+        assert!(message.contains("image/png"));
+    }
+
+    #[test]
+    fn attachments_are_added_to_a_multipart_mixed_envelope() {
+        let mut content = EmailContent::new("Subject", "<p>Hi</p>", "Hi");
+        content.attachments.push(EmailAttachment {
+            filename: "report.pdf".into(),
+            content_type: "application/pdf".into(),
+            data: b"%PDF-1.4".to_vec(),
+        });
+
+        let message = formatted(&content);
+
+        assert!(message.contains("multipart/mixed"));
+        assert!(message.contains("filename=\"report.pdf\""));
+        assert!(message.contains("application/pdf"));
+    }
+
+    #[test]
+    fn invalid_attachment_content_types_are_rejected() {
+        let mut content = EmailContent::new("Subject", "<p>Hi</p>", "Hi");
+        content.attachments.push(EmailAttachment {
+            filename: "report.pdf".into(),
+            content_type: "not a content type".into(),
+            data: Vec::new(),
+        });
+
+        let outcome = build_message(&email(), &email(), &content);
+        assert!(matches!(outcome, Err(DeliveryError::Permanent(_))));
+    }
+}
@@ -0,0 +1,128 @@
+use super::{DeliveryError, EmailContent, EmailSender, build_message};
+use crate::domain::SubscriberEmail;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+use lettre::transport::smtp::PoolConfig;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// 基于 tokio 的异步 SMTP 后端，复用连接池中的 TLS 会话
+pub struct SmtpEmailClient {
+    mailer: AsyncSmtpTransport<Tokio1Executor>,
+    // lettre 的连接池只限制空闲连接数，并发连接数由信号量限制
+    connection_permits: Semaphore,
+    sender: SubscriberEmail,
+}
+
+/// SMTP 连接池配置
+#[derive(Debug, Clone, Copy)]
+pub struct SmtpPoolOptions {
+    pub max_connections: u32,      // 最大并发连接数
+    pub idle_timeout: Duration,    // 空闲连接保留时长
+    pub request_timeout: Duration, // 单条 SMTP 命令的超时
+}
+
+impl SmtpEmailClient {
+    pub fn new(
+        smtp_host: String,
+        smtp_port: u16,
+        credentials: Credentials,
+        use_starttls: bool,
+        pool_options: SmtpPoolOptions,
+        sender: SubscriberEmail,
+    ) -> Self {
+        // 163：465 端口为隐式 TLS；587 为 STARTTLS
+        let builder = if use_starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp_host)
+                .expect("invalid SMTP host")
+                .port(smtp_port)
+        } else {
+            let tls = TlsParameters::new(smtp_host.clone()).expect("failed to create TLS params");
+            AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp_host)
+                .expect("invalid SMTP host")
+                .port(smtp_port)
+                .tls(Tls::Wrapper(tls))
+        };
+
+        let pool_config = PoolConfig::new()
+            .max_size(pool_options.max_connections)
+            .idle_timeout(pool_options.idle_timeout);
+        let mailer = builder
+            .credentials(credentials)
+            .timeout(Some(pool_options.request_timeout))
+            .pool_config(pool_config)
+            .build();
+
+        Self {
+            mailer,
+            connection_permits: Semaphore::new(pool_options.max_connections as usize),
+            sender,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl EmailSender for SmtpEmailClient {
+    async fn send_email(
+        &self,
+        recipient: &SubscriberEmail,
+        content: &EmailContent,
+    ) -> Result<(), DeliveryError> {
+        let email = build_message(&self.sender, recipient, content)?;
+
+        let _permit = self
+            .connection_permits
+            .acquire()
+            .await
+            .map_err(|e| DeliveryError::Transient(format!("SMTP connection pool closed: {}", e)))?;
+        self.mailer
+            .send(email)
+            .await
+            .map_err(classify_smtp_error)?;
+        Ok(())
+    }
+
+    /// 建立连接并完成 TLS 握手与 EHLO，不发送邮件
+    async fn check_connection(&self) -> Option<Result<(), String>> {
+        let outcome = match self.mailer.test_connection().await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("SMTP server did not accept the connection".to_string()),
+            Err(e) => Err(format!("SMTP connection error: {}", e)),
+        };
+        Some(outcome)
+    }
+}
+
+/// 5xx 回复为永久失败；4xx、连接、TLS、超时等其余错误按临时失败处理
+fn classify_smtp_error(e: lettre::transport::smtp::Error) -> DeliveryError {
+    let message = format!("SMTP send error: {}", e);
+    if e.is_permanent() {
+        DeliveryError::Permanent(message)
+    } else {
+        DeliveryError::Transient(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fake::{Fake, faker::internet::en::SafeEmail};
+
+    #[tokio::test]
+    async fn can_build_email_client_without_network() {
+        let sender = SubscriberEmail::parse(SafeEmail().fake()).unwrap();
+        let _client = SmtpEmailClient::new(
+            "smtp.163.com".to_string(),
+            465,
+            Credentials::new("test@163.com".to_string(), "authcode".to_string()),
+            false,
+            SmtpPoolOptions {
+                max_connections: 4,
+                idle_timeout: Duration::from_secs(60),
+                request_timeout: Duration::from_secs(10),
+            },
+            sender,
+        );
+    }
+}
@@ -0,0 +1,14 @@
+use crate::idempotency::delete_expired_idempotency_keys;
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// 定期清理过期的幂等键，正常情况下不会返回
+pub async fn run_idempotency_expiry_until_stopped(pool: PgPool, ttl: Duration, sweep_interval: Duration) {
+    loop {
+        match delete_expired_idempotency_keys(&pool, ttl).await {
+            Ok(n_deleted) => tracing::info!(n_deleted, "Swept expired idempotency keys"),
+            Err(e) => tracing::error!(error.message = %e, "Failed to sweep expired idempotency keys"),
+        }
+        tokio::time::sleep(sweep_interval).await;
+    }
+}
@@ -0,0 +1,348 @@
+use crate::domain::{SubscriberEmail, UnsubscribeToken};
+use crate::service::email_client::{DeliveryError, EmailSender};
+use crate::suppression::SuppressionList;
+use chrono::Utc;
+use rand::Rng;
+use secrecy::{ExposeSecret, SecretString};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::time::Duration;
+use tracing::{field::display, Span};
+use uuid::Uuid;
+
+/// 单次出队执行的结果
+pub enum ExecutionOutcome {
+    TaskCompleted,
+    EmptyQueue,
+}
+
+/// 投递失败后的重试策略：指数退避 + 抖动
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,    // 含首次投递在内的最大尝试次数
+    pub base_delay: Duration, // 第一次重试前的基础等待
+    pub max_delay: Duration,  // 单次等待的上限
+}
+
+impl RetryPolicy {
+    /// 第 `n_retries` 次失败后的等待时长：
+    /// 取 `min(max_delay, base_delay * 2^n_retries)`，再在其 [1/2, 1] 区间内随机抖动，
+    /// 避免大量失败任务在同一时刻集中重试
+    pub fn backoff(&self, n_retries: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(n_retries))
+            .min(self.max_delay);
+        let half = exponential / 2;
+        half + rand::thread_rng().gen_range(Duration::ZERO..=exponential - half)
+    }
+}
+
+/// 为每位订阅者生成带签名令牌的退订链接
+#[derive(Debug, Clone)]
+pub struct UnsubscribeLinks {
+    base_url: String,
+    hmac_secret: SecretString,
+}
+
+impl UnsubscribeLinks {
+    pub fn new(base_url: String, hmac_secret: SecretString) -> Self {
+        Self { base_url, hmac_secret }
+    }
+
+    pub fn link(&self, subscriber_id: Uuid) -> String {
+        let token = UnsubscribeToken::generate(subscriber_id, self.hmac_secret.expose_secret().as_bytes());
+        format!("{}/subscriptions/unsubscribe?token={}", self.base_url, token.as_ref())
+    }
+}
+
+/// 持续消费投递队列，正常情况下不会返回
+/// 多个实例可以并发运行：出队使用 `FOR UPDATE SKIP LOCKED`，同一行只会被一个 worker 处理
+pub async fn run_worker_until_stopped(
+    pool: PgPool,
+    email_client: std::sync::Arc<dyn EmailSender>,
+    retry_policy: RetryPolicy,
+    unsubscribe_links: UnsubscribeLinks,
+    suppression_list: std::sync::Arc<SuppressionList>,
+) {
+    loop {
+        match try_execute_task(&pool, email_client.as_ref(), &retry_policy, &unsubscribe_links, &suppression_list).await {
+            Ok(ExecutionOutcome::EmptyQueue) => {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+            }
+            Err(_) => {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            Ok(ExecutionOutcome::TaskCompleted) => {}
+        }
+    }
+}
+
+#[tracing::instrument(
+    skip_all,
+    fields(
+        newsletter_issue_id = tracing::field::Empty,
+        subscriber_email = tracing::field::Empty,
+        n_retries = tracing::field::Empty,
+    ),
+    err,
+)]
+pub async fn try_execute_task(
+    pool: &PgPool,
+    email_client: &dyn EmailSender,
+    retry_policy: &RetryPolicy,
+    unsubscribe_links: &UnsubscribeLinks,
+    suppression_list: &SuppressionList,
+) -> Result<ExecutionOutcome, sqlx::Error> {
+    let task = dequeue_task(pool).await?;
+    let Some((mut transaction, task)) = task else {
+        return Ok(ExecutionOutcome::EmptyQueue);
+    };
+    Span::current()
+        .record("newsletter_issue_id", display(task.newsletter_issue_id))
+        .record("subscriber_email", display(&task.subscriber_email))
+        .record("n_retries", task.n_retries);
+    match SubscriberEmail::parse(task.subscriber_email.clone()) {
+        // 以下查询都在出队事务中执行，每个任务只占用一个连接
+        Ok(email) => match get_confirmed_subscriber_id(&mut transaction, &task.subscriber_email).await? {
+            // 发布后才加入黑名单的地址同样不再投递
+            Some(subscriber_id) => match suppression_list.check(&mut *transaction, email.as_ref()).await? {
+                None => {
+                    let issue = get_issue(&mut transaction, task.newsletter_issue_id).await?;
+                    match email_client
+                        .send_bulk(
+                            &email,
+                            &issue.title,
+                            &issue.html_content,
+                            &issue.text_content,
+                            &unsubscribe_links.link(subscriber_id),
+                        )
+                        .await
+                    {
+                        Ok(()) => delete_task(&mut transaction, &task).await?,
+                        Err(e) => handle_delivery_failure(&mut transaction, &task, e, retry_policy).await?,
+                    }
+                }
+                Some(reason) => {
+                    tracing::info!(reason = %reason, "Skipping a suppressed subscriber");
+                    delete_task(&mut transaction, &task).await?;
+                }
+            },
+            None => {
+                tracing::info!("Skipping a subscriber who unsubscribed after the issue was published");
+                delete_task(&mut transaction, &task).await?;
+            }
+        },
+        Err(e) => {
+            tracing::error!(
+                error.message = %e,
+                "Skipping a confirmed subscriber. Their stored contact details are invalid",
+            );
+            delete_task(&mut transaction, &task).await?;
+        }
+    }
+    transaction.commit().await?;
+    Ok(ExecutionOutcome::TaskCompleted)
+}
+
+/// 临时失败且仍有剩余次数时推迟重试，否则移入死信表
+async fn handle_delivery_failure(
+    transaction: &mut PgTransaction,
+    task: &DeliveryTask,
+    error: DeliveryError,
+    retry_policy: &RetryPolicy,
+) -> Result<(), sqlx::Error> {
+    let attempts = task.n_retries as u32 + 1;
+    if error.is_transient() && attempts < retry_policy.max_attempts {
+        let delay = retry_policy.backoff(task.n_retries as u32);
+        tracing::warn!(
+            error.message = %error,
+            retry_in_seconds = delay.as_secs_f64(),
+            "Failed to deliver issue to a confirmed subscriber. Retrying later.",
+        );
+        schedule_retry(transaction, task, delay, &error.to_string()).await
+    } else {
+        tracing::error!(
+            error.message = %error,
+            attempts,
+            "Failed to deliver issue to a confirmed subscriber. Moving it to the dead letter table.",
+        );
+        move_to_dead_letters(transaction, task, &error.to_string()).await
+    }
+}
+
+type PgTransaction = Transaction<'static, Postgres>;
+
+struct DeliveryTask {
+    newsletter_issue_id: Uuid,
+    subscriber_email: String,
+    n_retries: i32,
+}
+
+#[tracing::instrument(skip_all)]
+async fn dequeue_task(pool: &PgPool) -> Result<Option<(PgTransaction, DeliveryTask)>, sqlx::Error> {
+    let mut transaction = pool.begin().await?;
+    let task = sqlx::query_as!(
+        DeliveryTask,
+        r#"
+        SELECT newsletter_issue_id, subscriber_email, n_retries
+        FROM issue_delivery_queue
+        WHERE execute_after <= now()
+        FOR UPDATE
+        SKIP LOCKED
+        LIMIT 1
+        "#,
+    )
+        .fetch_optional(&mut *transaction)
+        .await?;
+    Ok(task.map(|task| (transaction, task)))
+}
+
+#[tracing::instrument(skip_all)]
+async fn delete_task(transaction: &mut PgTransaction, task: &DeliveryTask) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        DELETE FROM issue_delivery_queue
+        WHERE
+            newsletter_issue_id = $1 AND
+            subscriber_email = $2
+        "#,
+        task.newsletter_issue_id,
+        task.subscriber_email,
+    )
+        .execute(&mut **transaction)
+        .await?;
+    Ok(())
+}
+
+#[tracing::instrument(skip_all)]
+async fn schedule_retry(
+    transaction: &mut PgTransaction,
+    task: &DeliveryTask,
+    delay: Duration,
+    last_error: &str,
+) -> Result<(), sqlx::Error> {
+    let execute_after = Utc::now()
+        + chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX);
+    sqlx::query!(
+        r#"
+        UPDATE issue_delivery_queue
+        SET
+            n_retries = n_retries + 1,
+            execute_after = $3,
+            last_error = $4
+        WHERE
+            newsletter_issue_id = $1 AND
+            subscriber_email = $2
+        "#,
+        task.newsletter_issue_id,
+        task.subscriber_email,
+        execute_after,
+        last_error,
+    )
+        .execute(&mut **transaction)
+        .await?;
+    Ok(())
+}
+
+#[tracing::instrument(skip_all)]
+async fn move_to_dead_letters(
+    transaction: &mut PgTransaction,
+    task: &DeliveryTask,
+    last_error: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        INSERT INTO issue_delivery_dead_letters (
+            newsletter_issue_id,
+            subscriber_email,
+            n_retries,
+            last_error,
+            failed_at
+        )
+        VALUES ($1, $2, $3, $4, now())
+        ON CONFLICT (newsletter_issue_id, subscriber_email) DO UPDATE
+        SET
+            n_retries = EXCLUDED.n_retries,
+            last_error = EXCLUDED.last_error,
+            failed_at = EXCLUDED.failed_at
+        "#,
+        task.newsletter_issue_id,
+        task.subscriber_email,
+        task.n_retries,
+        last_error,
+    )
+        .execute(&mut **transaction)
+        .await?;
+    delete_task(transaction, task).await
+}
+
+#[tracing::instrument(skip_all)]
+async fn get_confirmed_subscriber_id(
+    transaction: &mut PgTransaction,
+    email: &str,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let row = sqlx::query!(
+        r#"
+        SELECT id
+        FROM subscriptions
+        WHERE lower(email) = lower($1) AND status = 'confirmed'
+        "#,
+        email,
+    )
+        .fetch_optional(&mut **transaction)
+        .await?;
+    Ok(row.map(|row| row.id))
+}
+
+struct NewsletterIssue {
+    title: String,
+    text_content: String,
+    html_content: String,
+}
+
+#[tracing::instrument(skip_all)]
+async fn get_issue(transaction: &mut PgTransaction, issue_id: Uuid) -> Result<NewsletterIssue, sqlx::Error> {
+    let issue = sqlx::query_as!(
+        NewsletterIssue,
+        r#"
+        SELECT title, text_content, html_content
+        FROM newsletter_issues
+        WHERE newsletter_issue_id = $1
+        "#,
+        issue_id,
+    )
+        .fetch_one(&mut **transaction)
+        .await?;
+    Ok(issue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RetryPolicy;
+    use std::time::Duration;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(10),
+            max_delay: Duration::from_secs(300),
+        }
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_within_the_jitter_window() {
+        for n_retries in 0..4 {
+            let expected = Duration::from_secs(10 * 2u64.pow(n_retries));
+            let backoff = policy().backoff(n_retries);
+            assert!(backoff >= expected / 2 && backoff <= expected, "{:?}", backoff);
+        }
+    }
+
+    #[test]
+    fn backoff_is_capped_at_the_max_delay() {
+        for n_retries in [5, 10, 64, u32::MAX] {
+            let backoff = policy().backoff(n_retries);
+            assert!(backoff >= Duration::from_secs(150) && backoff <= Duration::from_secs(300));
+        }
+    }
+}
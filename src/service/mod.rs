@@ -1 +1,4 @@
-pub mod email_client;
\ No newline at end of file
+pub mod email_client;
+pub mod issue_delivery_worker;
+pub mod session_store;
+pub mod idempotency_expiry_worker;
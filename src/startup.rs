@@ -1,21 +1,157 @@
 use actix_web::{web, App, HttpRequest, HttpServer, Responder, dev::Server};
+use actix_web::cookie::Key;
+use actix_web::middleware::from_fn;
+use actix_session::SessionMiddleware;
+use actix_web_flash_messages::FlashMessagesFramework;
+use actix_web_flash_messages::storage::CookieMessageStore;
+use crate::authentication::{basic_authentication, reject_anonymous_users};
 use std::net::TcpListener;
 use sqlx::PgPool;
 use crate::routes;
 use tracing_actix_web::TracingLogger;
-use crate::service::email_client::EmailClient;
+use crate::service::email_client::{build_email_client, EmailSender};
+use crate::service::session_store::PgSessionStore;
+use crate::suppression::{DisposableDomains, SuppressionList};
+use crate::configuration::{DatabaseSettings, Settings};
+use std::sync::Arc;
+use secrecy::{ExposeSecret, SecretString};
 
-pub fn run(listener: TcpListener, connection_pool: PgPool, email_client: EmailClient) -> Result<Server, std::io::Error> {
+/// 组装完成、已绑定端口但尚未开始服务的应用
+/// 二进制与集成测试共用同一套启动流程
+pub struct Application {
+    port: u16,
+    server: Server,
+    connection_pool: PgPool,
+    email_client: Arc<dyn EmailSender>,
+    suppression_list: Arc<SuppressionList>,
+}
+
+impl Application {
+    /// 创建连接池、邮件客户端与拦截列表并绑定监听地址
+    /// `application.port` 为 0 时由操作系统分配空闲端口，可通过 `port()` 取得
+    pub async fn build(configuration: Settings) -> Result<Self, anyhow::Error> {
+        let connection_pool = get_connection_pool(&configuration.database);
+        let email_client = build_email_client(configuration.email.clone()).map_err(anyhow::Error::msg)?;
+        let disposable_domains = DisposableDomains::load(&configuration.suppression.disposable_domains_file)?;
+        tracing::info!("Loaded {} disposable email domains", disposable_domains.len());
+        let suppression_list = Arc::new(SuppressionList::new(disposable_domains));
+
+        let address = format!("{}:{}", configuration.application.host, configuration.application.port);
+        let listener = TcpListener::bind(address)?;
+        let port = listener.local_addr()?.port();
+        let server = run(
+            listener,
+            connection_pool.clone(),
+            email_client.clone(),
+            suppression_list.clone(),
+            configuration,
+        )?;
+        Ok(Self {
+            port,
+            server,
+            connection_pool,
+            email_client,
+            suppression_list,
+        })
+    }
+
+    /// 实际绑定的端口
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// 后台任务与 HTTP 服务共用的连接池
+    pub fn connection_pool(&self) -> PgPool {
+        self.connection_pool.clone()
+    }
+
+    pub fn email_client(&self) -> Arc<dyn EmailSender> {
+        self.email_client.clone()
+    }
+
+    pub fn suppression_list(&self) -> Arc<SuppressionList> {
+        self.suppression_list.clone()
+    }
+
+    /// 持续提供服务直到进程收到停止信号
+    pub async fn run_until_stopped(self) -> Result<(), std::io::Error> {
+        self.server.await
+    }
+}
+
+/// 惰性连接池：首次执行查询时才建立连接，数据库暂时不可用时应用仍可启动
+pub fn get_connection_pool(configuration: &DatabaseSettings) -> PgPool {
+    configuration.pool_options().connect_lazy_with(configuration.connect_options())
+}
+
+/// 应用对外访问地址，供路由拼接确认链接等使用
+pub struct ApplicationBaseUrl(pub String);
+
+/// 签名密钥，用于校验退订令牌
+pub struct HmacSecret(pub SecretString);
+
+fn run(
+    listener: TcpListener,
+    connection_pool: PgPool,
+    email_client: Arc<dyn EmailSender>,
+    suppression_list: Arc<SuppressionList>,
+    configuration: Settings,
+) -> Result<Server, std::io::Error> {
+    let hmac_secret = configuration.application.hmac_secret;
+    let secret_key = Key::try_from(hmac_secret.expose_secret().as_bytes()).map_err(|e| {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, format!("Invalid hmac_secret: {}", e))
+    })?;
+    let message_store = CookieMessageStore::builder(secret_key.clone()).build();
+    let message_framework = FlashMessagesFramework::builder(message_store).build();
+    let hmac_secret = web::Data::new(HmacSecret(hmac_secret));
     let connection_pool = web::Data::new(connection_pool);
-    let email_client = web::Data::new(email_client);
+    let email_client: web::Data<dyn EmailSender> = web::Data::from(email_client);
+    let base_url = web::Data::new(ApplicationBaseUrl(configuration.application.base_url));
+    let validation_policy = web::Data::new(configuration.validation.policy());
+    let health_settings = web::Data::new(configuration.health);
+    let suppression_list = web::Data::from(suppression_list);
     let server = HttpServer::new(move || {
         App::new()
+            .wrap(message_framework.clone())
+            .wrap(SessionMiddleware::new(
+                PgSessionStore::new(connection_pool.get_ref().clone()),
+                secret_key.clone(),
+            ))
             .wrap(TracingLogger::default())
             .route("/", web::get().to(greet))
             .route("/health", web::get().to(routes::health::health_check))
+            .route("/health/live", web::get().to(routes::health::liveness))
+            .route("/health/ready", web::get().to(routes::health::readiness))
             .route("/subscribe", web::post().to(routes::subscriptions::subscribe))
+            .route("/subscriptions/confirm", web::get().to(routes::subscriptions_confirm::confirm))
+            .route("/subscriptions/unsubscribe", web::get().to(routes::subscriptions_unsubscribe::unsubscribe_form))
+            .route("/subscriptions/unsubscribe", web::post().to(routes::subscriptions_unsubscribe::unsubscribe))
+            .route("/login", web::get().to(routes::login::login_form))
+            .route("/login", web::post().to(routes::login::login))
+            // 发布与管理接口需要管理员身份
+            .service(
+                web::resource("/newsletters")
+                    .wrap(from_fn(basic_authentication))
+                    .route(web::post().to(routes::newsletters::publish_newsletter)),
+            )
+            .service(
+                web::scope("/admin")
+                    .wrap(from_fn(reject_anonymous_users))
+                    .route("/dashboard", web::get().to(routes::admin::admin_dashboard))
+                    .route("/logout", web::post().to(routes::admin::log_out))
+                    .route("/dead_letters", web::get().to(routes::admin::list_dead_letters))
+                    .route("/dead_letters/requeue", web::post().to(routes::admin::requeue_dead_letter))
+                    .route("/blocklist", web::get().to(routes::admin::list_blocklist))
+                    .route("/blocklist", web::post().to(routes::admin::add_to_blocklist))
+                    .route("/blocklist/{id}", web::delete().to(routes::admin::remove_from_blocklist)),
+            )
             .app_data(connection_pool.clone())
             .app_data(email_client.clone())
+            .app_data(base_url.clone())
+            .app_data(hmac_secret.clone())
+            .app_data(validation_policy.clone())
+            .app_data(suppression_list.clone())
+            .app_data(health_settings.clone())
     })
         .listen(listener)?
         .run();
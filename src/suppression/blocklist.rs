@@ -0,0 +1,152 @@
+use crate::domain::SubscriberEmail;
+use chrono::{DateTime, Utc};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+/// 黑名单条目的匹配方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlocklistKind {
+    Address, // 完整地址
+    Domain,  // 域名及其所有子域名
+    Pattern, // 使用 `*` 通配的地址
+}
+
+impl BlocklistKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlocklistKind::Address => "address",
+            BlocklistKind::Domain => "domain",
+            BlocklistKind::Pattern => "pattern",
+        }
+    }
+}
+
+/// 校验并规范化后的黑名单规则
+#[derive(Debug, PartialEq, Eq)]
+pub struct BlocklistRule {
+    pub kind: BlocklistKind,
+    pub value: String,
+}
+
+impl BlocklistRule {
+    /// 含 `*` 视为通配模式，含 `@` 视为完整地址，否则视为域名；结果统一为小写
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("The blocklist entry cannot be empty.".into());
+        }
+        if s.chars().any(char::is_whitespace) {
+            return Err(format!("{} contains whitespace.", s));
+        }
+        let (kind, value) = if s.contains('*') {
+            (BlocklistKind::Pattern, s.to_lowercase())
+        } else if s.contains('@') {
+            let email = SubscriberEmail::parse(s.to_owned()).map_err(|e| e.to_string())?;
+            (BlocklistKind::Address, email.as_ref().to_lowercase())
+        } else {
+            let domain = idna::domain_to_ascii(s).map_err(|_| format!("{} is not a valid domain.", s))?;
+            (BlocklistKind::Domain, domain)
+        };
+        Ok(Self { kind, value })
+    }
+}
+
+/// 黑名单中的一条记录
+#[derive(serde::Serialize)]
+pub struct BlocklistEntry {
+    pub id: Uuid,
+    pub kind: String,
+    pub value: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// 写入黑名单，规则已存在时返回 `None`
+#[tracing::instrument(name = "Add a blocklist entry", skip(pool))]
+pub async fn add_blocklist_entry(pool: &PgPool, rule: &BlocklistRule) -> Result<Option<BlocklistEntry>, sqlx::Error> {
+    sqlx::query_as!(
+        BlocklistEntry,
+        r#"
+        INSERT INTO email_blocklist (id, kind, value, created_at)
+        VALUES ($1, $2, $3, now())
+        ON CONFLICT (kind, value) DO NOTHING
+        RETURNING id, kind, value, created_at
+        "#,
+        Uuid::new_v4(),
+        rule.kind.as_str(),
+        rule.value,
+    )
+        .fetch_optional(pool)
+        .await
+}
+
+#[tracing::instrument(name = "List blocklist entries", skip(pool))]
+pub async fn list_blocklist_entries(pool: &PgPool) -> Result<Vec<BlocklistEntry>, sqlx::Error> {
+    sqlx::query_as!(
+        BlocklistEntry,
+        r#"
+        SELECT id, kind, value, created_at
+        FROM email_blocklist
+        ORDER BY created_at DESC
+        "#,
+    )
+        .fetch_all(pool)
+        .await
+}
+
+/// 删除一条规则，记录不存在时返回 `false`
+#[tracing::instrument(name = "Remove a blocklist entry", skip(pool))]
+pub async fn remove_blocklist_entry(pool: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(r#"DELETE FROM email_blocklist WHERE id = $1"#, id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// 地址是否命中任一黑名单规则
+/// 通配模式在查询时转换为 `LIKE` 模式：先转义 `\`、`%`、`_`，再把 `*` 替换为 `%`
+#[tracing::instrument(name = "Check the email blocklist", skip(executor))]
+pub async fn is_blocklisted<'e>(executor: impl PgExecutor<'e>, email: &str) -> Result<bool, sqlx::Error> {
+    let domain = email.rsplit_once('@').map(|(_, domain)| domain).unwrap_or_default();
+    let record = sqlx::query!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1
+            FROM email_blocklist
+            WHERE
+                (kind = 'address' AND value = lower($1)) OR
+                (kind = 'domain' AND (value = lower($2) OR right(lower($2), length(value) + 1) = '.' || value)) OR
+                (kind = 'pattern' AND lower($1) LIKE replace(replace(replace(replace(value, '\', '\\'), '%', '\%'), '_', '\_'), '*', '%'))
+        ) AS "blocklisted!"
+        "#,
+        email,
+        domain,
+    )
+        .fetch_one(executor)
+        .await?;
+    Ok(record.blocklisted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BlocklistKind, BlocklistRule};
+    use claim::assert_err;
+
+    #[test]
+    fn the_kind_is_inferred_from_the_entry() {
+        let rule = |s| BlocklistRule::parse(s).unwrap();
+
+        assert_eq!(rule("Spammer@Example.com").kind, BlocklistKind::Address);
+        assert_eq!(rule("Spammer@Example.com").value, "spammer@example.com");
+        assert_eq!(rule("Example.COM").kind, BlocklistKind::Domain);
+        assert_eq!(rule("Bücher.example").value, "xn--bcher-kva.example");
+        assert_eq!(rule("*@*.SPAM.example").kind, BlocklistKind::Pattern);
+        assert_eq!(rule("*@*.SPAM.example").value, "*@*.spam.example");
+    }
+
+    #[test]
+    fn empty_and_malformed_entries_are_rejected() {
+        assert_err!(BlocklistRule::parse("  "));
+        assert_err!(BlocklistRule::parse("spam mer@example.com"));
+        assert_err!(BlocklistRule::parse("not-an-email@"));
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+/// 一次性邮箱域名列表，启动时从文件加载
+#[derive(Debug, Default)]
+pub struct DisposableDomains(HashSet<String>);
+
+impl DisposableDomains {
+    pub fn load(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// 每行一个域名，忽略空行与 `#` 开头的注释；域名按订阅地址的方式规范化
+    pub fn parse(contents: &str) -> Self {
+        let domains = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|domain| idna::domain_to_ascii(domain).unwrap_or_else(|_| domain.to_lowercase()))
+            .collect();
+        Self(domains)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// 域名本身或其任一上级域名在列表中即视为一次性域名
+    pub fn contains(&self, domain: &str) -> bool {
+        let mut domain = domain;
+        loop {
+            if self.0.contains(domain) {
+                return true;
+            }
+            match domain.split_once('.') {
+                Some((_, parent)) => domain = parent,
+                None => return false,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DisposableDomains;
+
+    #[test]
+    fn comments_and_blank_lines_are_ignored() {
+        let domains = DisposableDomains::parse("# throwaway domains\n\nmailinator.com\n  YOPMAIL.com  \n");
+
+        assert_eq!(domains.len(), 2);
+        assert!(domains.contains("mailinator.com"));
+        assert!(domains.contains("yopmail.com"));
+    }
+
+    #[test]
+    fn subdomains_of_listed_domains_are_matched() {
+        let domains = DisposableDomains::parse("mailinator.com");
+
+        assert!(domains.contains("eu.mailinator.com"));
+        assert!(!domains.contains("notmailinator.com"));
+        assert!(!domains.contains("com"));
+    }
+
+    #[test]
+    fn the_bundled_list_can_be_loaded() {
+        let domains = DisposableDomains::load("configuration/disposable_domains.txt").unwrap();
+
+        assert!(domains.contains("mailinator.com"));
+    }
+}
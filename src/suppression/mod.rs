@@ -0,0 +1,58 @@
+mod blocklist;
+mod disposable_domains;
+
+pub use blocklist::{
+    add_blocklist_entry, is_blocklisted, list_blocklist_entries, remove_blocklist_entry, BlocklistEntry,
+    BlocklistKind, BlocklistRule,
+};
+pub use disposable_domains::DisposableDomains;
+
+use sqlx::PgExecutor;
+
+/// 地址被拦截的原因
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum SuppressionReason {
+    #[error("Disposable email addresses are not accepted.")]
+    DisposableDomain,
+    #[error("This email address cannot subscribe to the newsletter.")]
+    Blocklisted,
+}
+
+impl SuppressionReason {
+    /// 机器可读的错误码，供前端映射到表单字段
+    pub fn code(&self) -> &'static str {
+        match self {
+            SuppressionReason::DisposableDomain => "disposable_domain",
+            SuppressionReason::Blocklisted => "blocked",
+        }
+    }
+}
+
+/// 订阅与投递前共用的拦截检查：启动时加载的一次性域名列表，以及管理员维护的黑名单
+pub struct SuppressionList {
+    disposable_domains: DisposableDomains,
+}
+
+impl SuppressionList {
+    pub fn new(disposable_domains: DisposableDomains) -> Self {
+        Self { disposable_domains }
+    }
+
+    /// `email` 应为 `SubscriberEmail` 规范化后的地址
+    /// 投递队列在出队事务中调用，避免每个任务同时占用两个连接
+    #[tracing::instrument(name = "Check the suppression list", skip(self, executor))]
+    pub async fn check<'e>(
+        &self,
+        executor: impl PgExecutor<'e>,
+        email: &str,
+    ) -> Result<Option<SuppressionReason>, sqlx::Error> {
+        let domain = email.rsplit_once('@').map(|(_, domain)| domain.to_ascii_lowercase());
+        if domain.is_some_and(|domain| self.disposable_domains.contains(&domain)) {
+            return Ok(Some(SuppressionReason::DisposableDomain));
+        }
+        if is_blocklisted(executor, email).await? {
+            return Ok(Some(SuppressionReason::Blocklisted));
+        }
+        Ok(None)
+    }
+}
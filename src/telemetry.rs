@@ -12,4 +12,14 @@ pub fn setup_logging(name: String, env_filter: String, sink: impl for<'a> MakeWr
     let formatting_layer = BunyanFormattingLayer::new(name, sink);
     let subscriber = Registry::default().with(env_filter).with(JsonStorageLayer).with(formatting_layer);
     set_global_default(subscriber).expect("Failed to set subscriber");
-}
\ No newline at end of file
+}
+
+/// 在阻塞线程池中执行 CPU 密集任务，并沿用当前的 tracing span
+pub fn spawn_blocking_with_tracing<F, R>(f: F) -> tokio::task::JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let current_span = tracing::Span::current();
+    tokio::task::spawn_blocking(move || current_span.in_scope(f))
+}
@@ -0,0 +1,129 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp};
+use wiremock::matchers::{any, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+async fn assert_subscription_is_rejected_with(app: &TestApp, email: &str, code: &str) {
+    let body = format!("name=cxw&email={}", email.replace('@', "%40"));
+
+    let response = app.post_subscriptions(body).await;
+
+    assert_eq!(400, response.status().as_u16(), "{} should be rejected", email);
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(problem["errors"][0]["field"], "email");
+    assert_eq!(problem["errors"][0]["code"], code);
+}
+
+#[actix_rt::test]
+async fn subscriptions_from_disposable_domains_are_rejected() {
+    let app = spawn_app().await;
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    assert_subscription_is_rejected_with(&app, "cxw@mailinator.com", "disposable_domain").await;
+    assert_subscription_is_rejected_with(&app, "cxw@eu.Mailinator.com", "disposable_domain").await;
+
+    let saved = sqlx::query!(r#"SELECT count(*) AS "count!" FROM subscriptions"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count subscriptions.");
+    assert_eq!(saved.count, 0);
+}
+
+#[actix_rt::test]
+async fn subscriptions_matching_a_blocklist_entry_are_rejected() {
+    let app = spawn_app().await;
+    for entry in ["spammer@progcxw.com", "junk.example", "bot*@*.example"] {
+        assert_eq!(app.post_blocklist(entry).await.status().as_u16(), 201);
+    }
+
+    assert_subscription_is_rejected_with(&app, "Spammer@progcxw.com", "blocked").await;
+    assert_subscription_is_rejected_with(&app, "cxw@mail.junk.example", "blocked").await;
+    assert_subscription_is_rejected_with(&app, "bot42@signup.example", "blocked").await;
+}
+
+#[actix_rt::test]
+async fn wildcard_characters_other_than_star_are_matched_literally() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+    app.post_blocklist("a_b*@progcxw.com").await;
+
+    assert_subscription_is_rejected_with(&app, "a_bc@progcxw.com", "blocked").await;
+    let response = app.post_subscriptions("name=cxw&email=axbc%40progcxw.com".into()).await;
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[actix_rt::test]
+async fn newsletters_are_not_delivered_to_blocklisted_subscribers() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    app.post_blocklist("progcxw.com").await;
+
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+    let response = app
+        .post_newsletters(serde_json::json!({
+            "title": "Newsletter title",
+            "content": {
+                "text": "Newsletter body as plain text",
+                "html": "<p>Newsletter body as HTML</p>",
+            }
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 202);
+    app.dispatch_all_pending_emails().await;
+
+    let queued = sqlx::query!(r#"SELECT count(*) AS "count!" FROM issue_delivery_queue"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(queued.count, 0);
+}
+
+#[actix_rt::test]
+async fn admins_can_list_and_remove_blocklist_entries() {
+    let app = spawn_app().await;
+    let entry: serde_json::Value = app.post_blocklist("Junk.Example").await.json().await.unwrap();
+    assert_eq!(entry["kind"], "domain");
+    assert_eq!(entry["value"], "junk.example");
+    assert_eq!(app.post_blocklist("junk.example").await.status().as_u16(), 409);
+    let response = app.post_blocklist("not-an-email@").await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(response.headers()["content-type"], "application/problem+json");
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(problem["status"], 400);
+    assert!(!problem["detail"].as_str().unwrap().is_empty());
+
+    let entries: serde_json::Value = app.get_blocklist().await.json().await.unwrap();
+    assert_eq!(entries.as_array().unwrap().len(), 1);
+
+    let id = entry["id"].as_str().unwrap();
+    assert_eq!(app.delete_blocklist_entry(id).await.status().as_u16(), 204);
+    assert_eq!(app.delete_blocklist_entry(id).await.status().as_u16(), 404);
+    let entries: serde_json::Value = app.get_blocklist().await.json().await.unwrap();
+    assert!(entries.as_array().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn anonymous_users_cannot_manage_the_blocklist() {
+    let app = spawn_app().await;
+
+    let response = app
+        .api_client
+        .post(format!("{}/admin/blocklist", &app.address))
+        .json(&serde_json::json!({ "entry": "junk.example" }))
+        .send()
+        .await
+        .unwrap();
+
+    assert_is_redirect_to(&response, "/login");
+}
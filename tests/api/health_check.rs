@@ -0,0 +1,103 @@
+use crate::helpers::spawn_app;
+
+#[actix_rt::test]
+async fn health_check_works() {
+    let app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(format!("{}/health", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert!(response.status().is_success());
+    assert_eq!(Some(0), response.content_length());
+}
+
+#[actix_rt::test]
+async fn liveness_does_not_depend_on_the_database() {
+    let app = spawn_app().await;
+    app.db_pool.close().await;
+
+    let response = app
+        .api_client
+        .get(format!("{}/health/live", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "pass");
+}
+
+#[actix_rt::test]
+async fn readiness_reports_every_check_with_its_latency() {
+    let app = spawn_app().await;
+
+    let response = app
+        .api_client
+        .get(format!("{}/health/ready", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 200);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "pass");
+    let checks = body["checks"].as_array().unwrap();
+    let names: Vec<_> = checks.iter().map(|c| c["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["database", "migrations", "pool"]);
+    for check in checks {
+        assert_eq!(check["status"], "pass");
+        assert!(check["latency_ms"].is_number());
+    }
+}
+
+#[actix_rt::test]
+async fn readiness_fails_when_a_migration_has_not_been_applied() {
+    let app = spawn_app().await;
+    sqlx::query("DELETE FROM _sqlx_migrations WHERE version = (SELECT max(version) FROM _sqlx_migrations)")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let response = app
+        .api_client
+        .get(format!("{}/health/ready", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 503);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "fail");
+    let migrations = body["checks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["name"] == "migrations")
+        .unwrap();
+    assert_eq!(migrations["status"], "fail");
+    assert!(migrations["detail"].as_str().unwrap().starts_with("Pending migrations:"));
+}
+
+#[actix_rt::test]
+async fn readiness_fails_when_the_database_is_unreachable() {
+    let app = spawn_app().await;
+    // 应用与测试共用同一个连接池，关闭后所有数据库检查都会失败
+    app.db_pool.close().await;
+
+    let response = app
+        .api_client
+        .get(format!("{}/health/ready", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    assert_eq!(response.status().as_u16(), 503);
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["checks"][0]["name"], "database");
+    assert_eq!(body["checks"][0]["status"], "fail");
+}
@@ -0,0 +1,388 @@
+use sqlx::{Executor, PgConnection, Connection, PgPool};
+use uuid::Uuid;
+use actix_try::{startup::Application, configuration::{load_configuration, ConfigurationSource, DatabaseSettings}, telemetry::setup_logging};
+use once_cell::sync::Lazy;
+use actix_try::authentication::compute_password_hash;
+use actix_try::service::email_client::EmailSender;
+use actix_try::service::issue_delivery_worker::{
+    try_execute_task, ExecutionOutcome, RetryPolicy, UnsubscribeLinks,
+};
+use actix_try::suppression::SuppressionList;
+use std::sync::Arc;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+static TRACING: Lazy<()> = Lazy::new(|| {
+    let default_filter_level = "info".to_string();
+    let subscriber_name = "test".to_string();
+
+    if std::env::var("TEST_LOG").is_ok() {
+        setup_logging(subscriber_name, default_filter_level, std::io::stdout);
+    } else {
+        setup_logging(subscriber_name, default_filter_level, std::io::sink);
+    }
+});
+
+pub struct TestApp {
+    pub address: String,
+    pub port: u16,
+    pub db_pool: PgPool,
+    pub email_server: MockServer,
+    pub email_client: Arc<dyn EmailSender>,
+    pub retry_policy: RetryPolicy,
+    pub unsubscribe_links: UnsubscribeLinks,
+    pub suppression_list: Arc<SuppressionList>,
+    pub test_user: TestUser,
+    // 保存 cookie 且不自动跟随重定向，用于模拟浏览器登录流程
+    pub api_client: reqwest::Client,
+}
+
+/// 测试用管理员账户
+pub struct TestUser {
+    pub user_id: Uuid,
+    pub username: String,
+    pub password: String,
+}
+
+impl TestUser {
+    pub fn generate() -> Self {
+        Self {
+            user_id: Uuid::new_v4(),
+            username: Uuid::new_v4().to_string(),
+            password: Uuid::new_v4().to_string(),
+        }
+    }
+
+    async fn store(&self, pool: &PgPool) {
+        let password_hash = compute_password_hash(&self.password).unwrap();
+        sqlx::query!(
+            "INSERT INTO users (user_id, username, password_hash) VALUES ($1, $2, $3)",
+            self.user_id,
+            self.username,
+            password_hash,
+        )
+            .execute(pool)
+            .await
+            .expect("Failed to store test user.");
+    }
+}
+
+/// 邮件正文中的确认链接
+pub struct ConfirmationLinks {
+    pub html: reqwest::Url,
+    pub plain_text: reqwest::Url,
+}
+
+impl TestApp {
+    pub async fn post_subscriptions(&self, body: String) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(format!("{}/subscribe", &self.address))
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_subscriptions_json(&self, body: serde_json::Value) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(format!("{}/subscribe", &self.address))
+            .header("Accept", "application/json")
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// 同步消费投递队列直至为空
+    pub async fn dispatch_all_pending_emails(&self) {
+        loop {
+            if let ExecutionOutcome::EmptyQueue =
+                try_execute_task(
+                    &self.db_pool,
+                    self.email_client.as_ref(),
+                    &self.retry_policy,
+                    &self.unsubscribe_links,
+                    &self.suppression_list,
+                )
+                    .await
+                    .unwrap()
+            {
+                break;
+            }
+        }
+    }
+
+    pub async fn post_login<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.api_client
+            .post(format!("{}/login", &self.address))
+            .form(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_login_html(&self) -> String {
+        self.api_client
+            .get(format!("{}/login", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .text()
+            .await
+            .unwrap()
+    }
+
+    pub async fn get_admin_dashboard(&self) -> reqwest::Response {
+        self.api_client
+            .get(format!("{}/admin/dashboard", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_dashboard_html(&self) -> String {
+        self.get_admin_dashboard().await.text().await.unwrap()
+    }
+
+    pub async fn post_logout(&self) -> reqwest::Response {
+        self.api_client
+            .post(format!("{}/admin/logout", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// 以测试管理员身份通过登录表单登录
+    pub async fn login(&self) {
+        let response = self
+            .post_login(&serde_json::json!({
+                "username": &self.test_user.username,
+                "password": &self.test_user.password,
+            }))
+            .await;
+        assert_is_redirect_to(&response, "/admin/dashboard");
+    }
+
+    pub async fn get_dead_letters(&self) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(format!("{}/admin/dead_letters", &self.address))
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_requeue_dead_letter(&self, body: serde_json::Value) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(format!("{}/admin/dead_letters/requeue", &self.address))
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_blocklist(&self) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(format!("{}/admin/blocklist", &self.address))
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_blocklist(&self, entry: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(format!("{}/admin/blocklist", &self.address))
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .json(&serde_json::json!({ "entry": entry }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_blocklist_entry(&self, id: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .delete(format!("{}/admin/blocklist/{}", &self.address, id))
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_newsletters(&self, body: serde_json::Value) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(format!("{}/newsletters", &self.address))
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_newsletters_with_idempotency_key(
+        &self,
+        body: serde_json::Value,
+        idempotency_key: &str,
+    ) -> reqwest::Response {
+        reqwest::Client::new()
+            .post(format!("{}/newsletters", &self.address))
+            .basic_auth(&self.test_user.username, Some(&self.test_user.password))
+            .header("Idempotency-Key", idempotency_key)
+            .json(&body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    /// 通过公开接口创建一个待确认的订阅者，返回其确认链接
+    pub async fn create_unconfirmed_subscriber(&self) -> ConfirmationLinks {
+        let body = "name=cxw%20prog&email=cxw%40progcxw.com";
+
+        let _mock_guard = Mock::given(path("/email"))
+            .and(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .named("Create unconfirmed subscriber")
+            .expect(1)
+            .mount_as_scoped(&self.email_server)
+            .await;
+        self.post_subscriptions(body.into())
+            .await
+            .error_for_status()
+            .unwrap();
+
+        let email_request = &self
+            .email_server
+            .received_requests()
+            .await
+            .unwrap()
+            .pop()
+            .unwrap();
+        self.get_confirmation_links(email_request)
+    }
+
+    pub async fn create_confirmed_subscriber(&self) {
+        let confirmation_links = self.create_unconfirmed_subscriber().await;
+        reqwest::get(confirmation_links.html)
+            .await
+            .unwrap()
+            .error_for_status()
+            .unwrap();
+    }
+
+    /// 从发往邮件 API 的请求中提取 `List-Unsubscribe` 头里的退订链接
+    pub fn get_unsubscribe_link(&self, email_request: &wiremock::Request) -> reqwest::Url {
+        let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+        let header = body["Headers"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|header| header["Name"] == "List-Unsubscribe")
+            .expect("No List-Unsubscribe header");
+        let link = header["Value"]
+            .as_str()
+            .unwrap()
+            .trim_start_matches('<')
+            .trim_end_matches('>');
+        reqwest::Url::parse(link).expect("Failed to parse unsubscribe link")
+    }
+
+    /// 从发往邮件 API 的请求中提取确认链接
+    pub fn get_confirmation_links(&self, email_request: &wiremock::Request) -> ConfirmationLinks {
+        let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+
+        let get_link = |s: &str| {
+            let links: Vec<_> = s
+                .split(|c: char| c.is_whitespace() || c == '"')
+                .filter(|s| s.contains("/subscriptions/confirm?"))
+                .collect();
+            assert_eq!(links.len(), 1);
+            let mut link = reqwest::Url::parse(links[0]).expect("Failed to parse confirmation link");
+            // 确认链接指向配置中的 base_url，只在本机时补上随机端口
+            assert_eq!(link.host_str().unwrap(), "127.0.0.1");
+            link.set_port(Some(self.port)).unwrap();
+            link
+        };
+
+        let html = get_link(body["HtmlBody"].as_str().unwrap());
+        let plain_text = get_link(body["TextBody"].as_str().unwrap());
+        ConfirmationLinks { html, plain_text }
+    }
+}
+
+/// 测试应用配置中被禁止订阅的邮箱域名
+pub const BLOCKED_EMAIL_DOMAIN: &str = "blocked.example";
+
+pub async fn spawn_app() -> TestApp {
+    Lazy::force(&TRACING);
+    let source = ConfigurationSource {
+        environment: Some("test".into()),
+        ..ConfigurationSource::current_dir().expect("Failed to determine the current directory")
+    };
+    let mut configuration = load_configuration(&source).expect("Failed to read configuration");
+    configuration.database.database_name = Uuid::new_v4().to_string();
+    configuration_database(&configuration.database).await;
+
+    let email_server = MockServer::start().await;
+    configuration.email.api_base_url = email_server.uri();
+    // 用于验证配置中的校验规则会生效
+    configuration.validation.blocked_email_domains = vec![BLOCKED_EMAIL_DOMAIN.into()];
+
+    let application = Application::build(configuration.clone())
+        .await
+        .expect("Failed to build application.");
+    let port = application.port();
+    let address = format!("http://127.0.0.1:{}", port);
+    let email_client = application.email_client();
+    let suppression_list = application.suppression_list();
+    let db_pool = application.connection_pool();
+    tokio::spawn(application.run_until_stopped());
+
+    let unsubscribe_links = UnsubscribeLinks::new(
+        address.clone(),
+        configuration.application.hmac_secret.clone(),
+    );
+    let test_app = TestApp {
+        address,
+        port,
+        db_pool,
+        email_server,
+        email_client,
+        retry_policy: configuration.delivery.retry_policy(),
+        unsubscribe_links,
+        suppression_list,
+        test_user: TestUser::generate(),
+        api_client: reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .cookie_store(true)
+            .build()
+            .unwrap(),
+    };
+    test_app.test_user.store(&test_app.db_pool).await;
+    test_app
+}
+
+pub fn assert_is_redirect_to(response: &reqwest::Response, location: &str) {
+    assert_eq!(response.status().as_u16(), 303);
+    assert_eq!(response.headers().get("Location").unwrap(), location);
+}
+
+pub async fn configuration_database(config: &DatabaseSettings) {
+    let mut connection = PgConnection::connect_with(&config.connect_options_without_db())
+        .await
+        .expect("Failed to connect to Postgres");
+    connection.execute(format!(r#"CREATE DATABASE "{}";"#, config.database_name).as_str())
+        .await
+        .expect("Failed to create database.");
+    let pool = PgPool::connect_with(config.connect_options())
+        .await
+        .expect("Failed to connect to Postgres.");
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to migrate the database");
+}
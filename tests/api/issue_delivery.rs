@@ -0,0 +1,205 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app, TestApp};
+use actix_try::service::issue_delivery_worker::{try_execute_task, ExecutionOutcome};
+use sqlx::postgres::PgPoolOptions;
+use std::time::Duration;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+async fn publish_newsletter(app: &TestApp) {
+    let newsletter_request_body = serde_json::json!({
+        "title": "Newsletter title",
+        "content": {
+            "text": "Newsletter body as plain text",
+            "html": "<p>Newsletter body as HTML</p>",
+        }
+    });
+    let response = app.post_newsletters(newsletter_request_body).await;
+    assert_eq!(response.status().as_u16(), 202);
+}
+
+#[actix_rt::test]
+async fn transient_failures_are_rescheduled_with_backoff() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    publish_newsletter(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    app.dispatch_all_pending_emails().await;
+
+    let task = sqlx::query!(
+        r#"
+        SELECT n_retries, execute_after > now() AS "is_delayed!", last_error
+        FROM issue_delivery_queue
+        "#
+    )
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("The delivery should still be queued.");
+    assert_eq!(task.n_retries, 1);
+    assert!(task.is_delayed);
+    assert!(task.last_error.is_some());
+}
+
+#[actix_rt::test]
+async fn permanent_failures_are_moved_to_the_dead_letter_table() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    publish_newsletter(&app).await;
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(422))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    app.dispatch_all_pending_emails().await;
+
+    let queued = sqlx::query!(r#"SELECT count(*) AS "count!" FROM issue_delivery_queue"#)
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(queued.count, 0);
+    let dead_letters: serde_json::Value = app.get_dead_letters().await.json().await.unwrap();
+    let dead_letters = dead_letters.as_array().unwrap();
+    assert_eq!(dead_letters.len(), 1);
+    assert_eq!(dead_letters[0]["subscriber_email"], "cxw@progcxw.com");
+}
+
+#[actix_rt::test]
+async fn deliveries_are_dead_lettered_once_retries_are_exhausted() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    publish_newsletter(&app).await;
+    // 模拟已经重试到最后一次
+    sqlx::query!(
+        "UPDATE issue_delivery_queue SET n_retries = $1",
+        app.retry_policy.max_attempts as i32 - 1,
+    )
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(503))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    app.dispatch_all_pending_emails().await;
+
+    let dead_letter = sqlx::query!("SELECT n_retries FROM issue_delivery_dead_letters")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("The delivery should have been dead lettered.");
+    assert_eq!(dead_letter.n_retries, app.retry_policy.max_attempts as i32 - 1);
+}
+
+#[actix_rt::test]
+async fn requeued_dead_letters_are_delivered_again() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    publish_newsletter(&app).await;
+
+    let rejection = Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(422))
+        .expect(1)
+        .mount_as_scoped(&app.email_server)
+        .await;
+    app.dispatch_all_pending_emails().await;
+    drop(rejection);
+
+    let dead_letters: serde_json::Value = app.get_dead_letters().await.json().await.unwrap();
+    let response = app
+        .post_requeue_dead_letter(serde_json::json!({
+            "newsletter_issue_id": dead_letters[0]["newsletter_issue_id"],
+            "subscriber_email": dead_letters[0]["subscriber_email"],
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 202);
+
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    app.dispatch_all_pending_emails().await;
+
+    let dead_letters: serde_json::Value = app.get_dead_letters().await.json().await.unwrap();
+    assert!(dead_letters.as_array().unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn requeueing_an_unknown_dead_letter_returns_404() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_requeue_dead_letter(serde_json::json!({
+            "newsletter_issue_id": uuid::Uuid::new_v4(),
+            "subscriber_email": "cxw@progcxw.com",
+        }))
+        .await;
+
+    assert_eq!(response.status().as_u16(), 404);
+}
+
+#[actix_rt::test]
+async fn dead_letter_admin_routes_require_authorization() {
+    let app = spawn_app().await;
+
+    // 匿名请求被重定向到登录页
+    let response = app
+        .api_client
+        .get(format!("{}/admin/dead_letters", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_is_redirect_to(&response, "/login");
+
+    // 错误的 Basic 凭据仍返回 401
+    let response = app
+        .api_client
+        .get(format!("{}/admin/dead_letters", &app.address))
+        .basic_auth(&app.test_user.username, Some("wrong-password"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    assert_eq!(401, response.status().as_u16());
+}
+
+#[actix_rt::test]
+async fn a_delivery_needs_only_one_pool_connection() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    publish_newsletter(&app).await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+    // 只有一个连接时，若出队事务之外还要再借连接，任务会一直等到获取超时
+    let single_connection_pool = PgPoolOptions::new()
+        .max_connections(1)
+        .acquire_timeout(Duration::from_secs(2))
+        .connect_with((*app.db_pool.connect_options()).clone())
+        .await
+        .unwrap();
+
+    let outcome = try_execute_task(
+        &single_connection_pool,
+        app.email_client.as_ref(),
+        &app.retry_policy,
+        &app.unsubscribe_links,
+        &app.suppression_list,
+    )
+        .await;
+
+    assert!(matches!(outcome, Ok(ExecutionOutcome::TaskCompleted)));
+}
@@ -0,0 +1,65 @@
+use crate::helpers::{assert_is_redirect_to, spawn_app};
+
+#[actix_rt::test]
+async fn an_error_flash_message_is_set_on_failure() {
+    let app = spawn_app().await;
+
+    let login_body = serde_json::json!({
+        "username": "random-username",
+        "password": "random-password",
+    });
+    let response = app.post_login(&login_body).await;
+    assert_is_redirect_to(&response, "/login");
+
+    let html_page = app.get_login_html().await;
+    assert!(html_page.contains("<p><i>Authentication failed.</i></p>"));
+
+    // 刷新页面后消息不再出现
+    let html_page = app.get_login_html().await;
+    assert!(!html_page.contains("Authentication failed."));
+}
+
+#[actix_rt::test]
+async fn a_wrong_password_and_an_unknown_username_get_the_same_message() {
+    let app = spawn_app().await;
+
+    app.post_login(&serde_json::json!({
+        "username": "random-username",
+        "password": &app.test_user.password,
+    }))
+        .await;
+    let unknown_username_page = app.get_login_html().await;
+
+    app.post_login(&serde_json::json!({
+        "username": &app.test_user.username,
+        "password": "wrong-password",
+    }))
+        .await;
+    let wrong_password_page = app.get_login_html().await;
+
+    assert_eq!(unknown_username_page, wrong_password_page);
+}
+
+#[actix_rt::test]
+async fn redirect_to_admin_dashboard_after_login_success() {
+    let app = spawn_app().await;
+
+    app.login().await;
+
+    let html_page = app.get_admin_dashboard_html().await;
+    assert!(html_page.contains(&format!("Welcome {}", app.test_user.username)));
+}
+
+#[actix_rt::test]
+async fn the_session_state_is_stored_in_postgres() {
+    let app = spawn_app().await;
+
+    app.login().await;
+
+    let n_sessions = sqlx::query!("SELECT COUNT(*) AS \"count!\" FROM sessions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap()
+        .count;
+    assert_eq!(n_sessions, 1);
+}
@@ -0,0 +1,11 @@
+mod admin_dashboard;
+mod blocklist;
+mod health_check;
+mod helpers;
+mod issue_delivery;
+mod login;
+mod newsletters;
+mod print_config;
+mod subscriptions;
+mod subscriptions_confirm;
+mod subscriptions_unsubscribe;
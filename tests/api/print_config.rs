@@ -0,0 +1,18 @@
+use std::process::Command;
+
+#[test]
+fn print_config_writes_only_json_to_stdout() {
+    // 弃用的 `APP_` 前缀会记录警告，确保打印配置时确实有日志输出
+    let output = Command::new(env!("CARGO_BIN_EXE_actix-try"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .args(["--environment", "test", "--print-config"])
+        .env("APP_APPLICATION__PORT", "8001")
+        .output()
+        .expect("Failed to run the binary.");
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let configuration: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("stdout is not valid JSON");
+    assert_eq!(configuration["application"]["port"], 8001);
+    assert!(!output.stderr.is_empty());
+}
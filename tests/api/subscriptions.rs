@@ -0,0 +1,453 @@
+use crate::helpers::{spawn_app, BLOCKED_EMAIL_DOMAIN};
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+#[actix_rt::test]
+async fn subscribe_return_success_for_valid_form_data() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    let body = "name=cxw%20prog&email=cxw%40progcxw.com";
+    let response = app.post_subscriptions(body.into()).await;
+
+    assert_eq!(200, response.status().as_u16());
+}
+
+#[actix_rt::test]
+async fn subscribe_persists_the_new_subscriber() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    let body = "name=cxw%20prog&email=cxw%40progcxw.com";
+    app.post_subscriptions(body.into()).await;
+
+    let saved = sqlx::query!("SELECT email, name, status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved subscription.");
+    assert_eq!(saved.email, "cxw@progcxw.com");
+    assert_eq!(saved.name, "cxw prog");
+    assert_eq!(saved.status, "pending_confirmation");
+}
+
+#[actix_rt::test]
+async fn subscribe_return_400_when_data_is_missing() {
+    let app = spawn_app().await;
+    let test_cases = vec![
+        ("name=cxw%20prog", "missing the email"),
+        ("email=cxw%40progcxw.com", "missing the name"),
+        ("", "missing both name and email"),
+    ];
+
+    for (invalid_body, error_message) in test_cases {
+        let response = app.post_subscriptions(invalid_body.into()).await;
+
+        assert_eq!(400, response.status().as_u16(), "Bad error: {}", error_message);
+    }
+}
+
+#[actix_rt::test]
+async fn subscribe_sends_a_confirmation_email_with_a_link() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let body = "name=cxw%20prog&email=cxw%40progcxw.com";
+    app.post_subscriptions(body.into()).await;
+
+    let email_request = &app.email_server.received_requests().await.unwrap()[0];
+    let links = app.get_confirmation_links(email_request);
+    assert_eq!(links.html, links.plain_text);
+}
+
+#[actix_rt::test]
+async fn subscribe_does_not_persist_anything_if_the_email_cannot_be_sent() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(500))
+        .mount(&app.email_server)
+        .await;
+
+    let body = "name=cxw%20prog&email=cxw%40progcxw.com";
+    let response = app.post_subscriptions(body.into()).await;
+
+    assert_eq!(500, response.status().as_u16());
+    let saved = sqlx::query!("SELECT count(*) AS \"count!\" FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count subscriptions.");
+    assert_eq!(saved.count, 0);
+}
+
+#[actix_rt::test]
+async fn subscribe_returns_a_problem_json_listing_every_invalid_field() {
+    let app = spawn_app().await;
+    let test_cases = vec![
+        ("name=&email=cxw%40progcxw.com", vec![("name", "empty")]),
+        ("name=cxw%20prog&email=definitely-not-an-email", vec![("email", "invalid_format")]),
+        ("name=%3Cscript%3E&email=", vec![("email", "empty"), ("name", "forbidden_character")]),
+        ("name=cxw%E2%80%AEgorp&email=cxw%40progcxw.com", vec![("name", "forbidden_character")]),
+        ("", vec![("email", "empty"), ("name", "empty")]),
+    ];
+
+    for (invalid_body, expected_errors) in test_cases {
+        let response = app.post_subscriptions(invalid_body.into()).await;
+
+        assert_eq!(400, response.status().as_u16());
+        assert_eq!(response.headers()["Content-Type"], "application/problem+json");
+        let problem: serde_json::Value = response.json().await.unwrap();
+        assert_eq!(problem["status"], 400);
+        let errors: Vec<_> = problem["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| (e["field"].as_str().unwrap(), e["code"].as_str().unwrap()))
+            .collect();
+        assert_eq!(errors, expected_errors, "Unexpected errors for {:?}", invalid_body);
+    }
+}
+
+#[actix_rt::test]
+async fn subscribe_rejects_emails_from_domains_blocked_in_the_configuration() {
+    let app = spawn_app().await;
+    let body = format!("name=cxw&email=cxw%40mail.{}", BLOCKED_EMAIL_DOMAIN);
+
+    let response = app.post_subscriptions(body).await;
+
+    assert_eq!(400, response.status().as_u16());
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(problem["errors"][0]["field"], "email");
+    assert_eq!(problem["errors"][0]["code"], "domain_not_allowed");
+    let saved = sqlx::query!("SELECT count(*) AS \"count!\" FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to count subscriptions.");
+    assert_eq!(saved.count, 0);
+}
+
+#[actix_rt::test]
+async fn names_longer_than_256_characters_are_reported_as_too_long() {
+    let app = spawn_app().await;
+    let body = format!("name={}&email=cxw%40progcxw.com", "a".repeat(257));
+
+    let response = app.post_subscriptions(body).await;
+
+    assert_eq!(400, response.status().as_u16());
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(problem["errors"][0]["code"], "too_long");
+}
+
+#[actix_rt::test]
+async fn subscribe_fails_if_there_is_a_fatal_database_error() {
+    let app = spawn_app().await;
+    // 破坏表结构，模拟存储层故障
+    sqlx::query!("ALTER TABLE subscription_tokens DROP COLUMN subscription_token;")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+
+    let body = "name=cxw%20prog&email=cxw%40progcxw.com";
+    let response = app.post_subscriptions(body.into()).await;
+
+    assert_eq!(500, response.status().as_u16());
+}
+
+#[actix_rt::test]
+async fn subscribe_accepts_json_bodies_and_replies_with_json() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    let response = app
+        .post_subscriptions_json(serde_json::json!({
+            "name": "cxw prog",
+            "email": "cxw@progcxw.com",
+        }))
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.unwrap();
+    let saved = sqlx::query!("SELECT id, email, status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .expect("Failed to fetch saved subscription.");
+    assert_eq!(saved.email, "cxw@progcxw.com");
+    assert_eq!(body["subscriber_id"], saved.id.to_string());
+    assert_eq!(body["status"], "accepted");
+}
+
+#[actix_rt::test]
+async fn subscribe_replies_with_json_to_form_posts_that_accept_json() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/subscribe", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept", "application/json")
+        .body("name=cxw%20prog&email=cxw%40progcxw.com")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(200, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "accepted");
+}
+
+#[actix_rt::test]
+async fn accept_header_parameters_do_not_prevent_a_json_reply() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/subscribe", &app.address))
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .header("Accept", "application/json; charset=utf-8")
+        .body("name=cxw%20prog&email=cxw%40progcxw.com")
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(200, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "accepted");
+}
+
+#[actix_rt::test]
+async fn subscribe_validates_json_bodies_like_forms() {
+    let app = spawn_app().await;
+
+    let response = app
+        .post_subscriptions_json(serde_json::json!({ "name": "", "email": "not-an-email" }))
+        .await;
+
+    assert_eq!(400, response.status().as_u16());
+    let problem: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(problem["errors"].as_array().unwrap().len(), 2);
+}
+
+#[actix_rt::test]
+async fn subscribe_rejects_unsupported_content_types_with_a_415() {
+    let app = spawn_app().await;
+    let test_cases = vec![Some("text/plain"), Some("application/xml"), None];
+
+    for content_type in test_cases {
+        let mut request = reqwest::Client::new()
+            .post(format!("{}/subscribe", &app.address))
+            .body("name=cxw%20prog&email=cxw%40progcxw.com");
+        if let Some(content_type) = content_type {
+            request = request.header("Content-Type", content_type);
+        }
+        let response = request.send().await.unwrap();
+
+        assert_eq!(415, response.status().as_u16(), "Content type: {:?}", content_type);
+    }
+}
+
+#[actix_rt::test]
+async fn subscribing_twice_while_pending_resends_the_confirmation_email() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(2)
+        .mount(&app.email_server)
+        .await;
+
+    let body = "name=cxw%20prog&email=cxw%40progcxw.com";
+    assert_eq!(200, app.post_subscriptions(body.into()).await.status().as_u16());
+    assert_eq!(200, app.post_subscriptions(body.into()).await.status().as_u16());
+
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.len(), 1);
+    assert_eq!(saved[0].status, "pending_confirmation");
+    // 每次重发都会生成新的确认令牌
+    let email_requests = app.email_server.received_requests().await.unwrap();
+    let first = app.get_confirmation_links(&email_requests[0]);
+    let second = app.get_confirmation_links(&email_requests[1]);
+    assert_ne!(first.html, second.html);
+}
+
+#[actix_rt::test]
+async fn json_replies_do_not_reveal_whether_an_address_is_subscribed() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    let body = serde_json::json!({ "name": "cxw prog", "email": "cxw@progcxw.com" });
+
+    let first: serde_json::Value = app.post_subscriptions_json(body.clone()).await.json().await.unwrap();
+    let second: serde_json::Value = app.post_subscriptions_json(body).await.json().await.unwrap();
+
+    let saved = sqlx::query!("SELECT id FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(first["status"], second["status"]);
+    assert_ne!(first["subscriber_id"], second["subscriber_id"]);
+    for reply in [&first, &second] {
+        assert_ne!(reply["subscriber_id"], saved.id.to_string());
+        assert_eq!(reply.as_object().unwrap().len(), 2);
+    }
+}
+
+#[actix_rt::test]
+async fn subscribing_a_confirmed_address_succeeds_silently() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    Mock::given(path("/email"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+
+    let response = app
+        .post_subscriptions_json(serde_json::json!({
+            "name": "cxw prog",
+            "email": "cxw@progcxw.com",
+        }))
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let body: serde_json::Value = response.json().await.unwrap();
+    assert_eq!(body["status"], "accepted");
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.status, "confirmed");
+}
+
+#[actix_rt::test]
+async fn subscribing_an_unsubscribed_address_opts_back_in() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    sqlx::query!("UPDATE subscriptions SET status = 'unsubscribed', unsubscribed_at = now()")
+        .execute(&app.db_pool)
+        .await
+        .unwrap();
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&app.email_server)
+        .await;
+
+    let body = "name=cxw%20prog&email=cxw%40progcxw.com";
+    let response = app.post_subscriptions(body.into()).await;
+
+    assert_eq!(200, response.status().as_u16());
+    let saved = sqlx::query!("SELECT status, unsubscribed_at FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.status, "pending_confirmation");
+    assert!(saved.unsubscribed_at.is_none());
+
+    // 需要点击新的确认链接才会恢复订阅
+    let email_request = &app.email_server.received_requests().await.unwrap().pop().unwrap();
+    let confirmation_links = app.get_confirmation_links(email_request);
+    reqwest::get(confirmation_links.html).await.unwrap().error_for_status().unwrap();
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.status, "confirmed");
+}
+
+#[actix_rt::test]
+async fn concurrent_duplicate_subscriptions_do_not_fail() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    let body = "name=cxw%20prog&email=cxw%40progcxw.com";
+    let (response1, response2) = tokio::join!(
+        app.post_subscriptions(body.into()),
+        app.post_subscriptions(body.into()),
+    );
+
+    assert_eq!(200, response1.status().as_u16());
+    assert_eq!(200, response2.status().as_u16());
+    let saved = sqlx::query!("SELECT count(*) AS \"count!\" FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.count, 1);
+}
+
+#[actix_rt::test]
+async fn emails_differing_only_in_case_are_the_same_subscriber() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    let first = app.post_subscriptions("name=cxw%20prog&email=Cxw%40ProgCxw.com".into()).await;
+    let second = app.post_subscriptions("name=cxw%20prog&email=%20cxw%40progcxw.com%20".into()).await;
+
+    assert_eq!(200, first.status().as_u16());
+    assert_eq!(200, second.status().as_u16());
+    let saved = sqlx::query!("SELECT email FROM subscriptions")
+        .fetch_all(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.len(), 1);
+    // 保留首次提交时本地部分的大小写，域名统一为小写
+    assert_eq!(saved[0].email, "Cxw@progcxw.com");
+}
+
+#[actix_rt::test]
+async fn internationalized_domains_are_stored_as_punycode() {
+    let app = spawn_app().await;
+    Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&app.email_server)
+        .await;
+
+    let response = app
+        .post_subscriptions_json(serde_json::json!({
+            "name": "cxw prog",
+            "email": "cxw@bücher.example",
+        }))
+        .await;
+
+    assert_eq!(200, response.status().as_u16());
+    let saved = sqlx::query!("SELECT email FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.email, "cxw@xn--bcher-kva.example");
+}
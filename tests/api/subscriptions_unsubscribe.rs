@@ -0,0 +1,137 @@
+use crate::helpers::{spawn_app, TestApp};
+use wiremock::matchers::{any, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+/// 发布一期 newsletter 并投递，返回发给订阅者的退订链接
+async fn deliver_newsletter_and_get_unsubscribe_link(app: &TestApp) -> reqwest::Url {
+    let _mock_guard = Mock::given(path("/email"))
+        .and(method("POST"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount_as_scoped(&app.email_server)
+        .await;
+    let response = app
+        .post_newsletters(serde_json::json!({
+            "title": "Newsletter title",
+            "content": {
+                "text": "Newsletter body as plain text",
+                "html": "<p>Newsletter body as HTML</p>",
+            }
+        }))
+        .await;
+    assert_eq!(response.status().as_u16(), 202);
+    app.dispatch_all_pending_emails().await;
+
+    let email_request = app.email_server.received_requests().await.unwrap().pop().unwrap();
+    app.get_unsubscribe_link(&email_request)
+}
+
+#[actix_rt::test]
+async fn newsletter_emails_carry_one_click_unsubscribe_headers() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+
+    let unsubscribe_link = deliver_newsletter_and_get_unsubscribe_link(&app).await;
+
+    assert_eq!(unsubscribe_link.path(), "/subscriptions/unsubscribe");
+    let email_request = app.email_server.received_requests().await.unwrap().pop().unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&email_request.body).unwrap();
+    let one_click = body["Headers"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|header| header["Name"] == "List-Unsubscribe-Post")
+        .unwrap();
+    assert_eq!(one_click["Value"], "List-Unsubscribe=One-Click");
+}
+
+#[actix_rt::test]
+async fn the_unsubscribe_page_does_not_change_the_subscription() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    let unsubscribe_link = deliver_newsletter_and_get_unsubscribe_link(&app).await;
+
+    let response = reqwest::get(unsubscribe_link).await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.text().await.unwrap().contains(r#"method="post""#));
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.status, "confirmed");
+}
+
+#[actix_rt::test]
+async fn one_click_unsubscribe_marks_the_subscriber_as_unsubscribed() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    let unsubscribe_link = deliver_newsletter_and_get_unsubscribe_link(&app).await;
+
+    // RFC 8058：邮件客户端以表单形式 POST 到 List-Unsubscribe 中的链接
+    let response = reqwest::Client::new()
+        .post(unsubscribe_link.clone())
+        .form(&[("List-Unsubscribe", "One-Click")])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+
+    let saved = sqlx::query!("SELECT status, unsubscribed_at FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.status, "unsubscribed");
+    assert!(saved.unsubscribed_at.is_some());
+
+    // 重复退订同样成功
+    let response = reqwest::Client::new().post(unsubscribe_link).send().await.unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+}
+
+#[actix_rt::test]
+async fn unsubscribed_subscribers_no_longer_receive_newsletters() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    let unsubscribe_link = deliver_newsletter_and_get_unsubscribe_link(&app).await;
+    reqwest::Client::new()
+        .post(unsubscribe_link)
+        .send()
+        .await
+        .unwrap()
+        .error_for_status()
+        .unwrap();
+
+    Mock::given(any())
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&app.email_server)
+        .await;
+    app.post_newsletters(serde_json::json!({
+        "title": "Newsletter title",
+        "content": {
+            "text": "Newsletter body as plain text",
+            "html": "<p>Newsletter body as HTML</p>",
+        }
+    }))
+        .await;
+    app.dispatch_all_pending_emails().await;
+}
+
+#[actix_rt::test]
+async fn tampered_unsubscribe_tokens_are_rejected_with_a_401() {
+    let app = spawn_app().await;
+    app.create_confirmed_subscriber().await;
+    let mut unsubscribe_link = deliver_newsletter_and_get_unsubscribe_link(&app).await;
+    let token = unsubscribe_link.query_pairs().next().unwrap().1.to_string();
+    unsubscribe_link.set_query(Some(&format!("token={}x", token)));
+
+    let response = reqwest::Client::new().post(unsubscribe_link).send().await.unwrap();
+
+    assert_eq!(response.status().as_u16(), 401);
+    let saved = sqlx::query!("SELECT status FROM subscriptions")
+        .fetch_one(&app.db_pool)
+        .await
+        .unwrap();
+    assert_eq!(saved.status, "confirmed");
+}